@@ -0,0 +1,84 @@
+use crate::{ast, diagnostic, hlir, Diagnostic, Operator, Result, Span};
+
+#[derive(Debug)]
+pub struct Assignment {
+	span: Span,
+	target: Box<dyn ast::Node>,
+	value: Box<dyn ast::Node>,
+	/// The operator of a compound assignment, like `+` in `x += 1`.
+	op: Option<Operator>,
+}
+
+impl Assignment {
+	pub fn new(
+		span: Span,
+		target: Box<dyn ast::Node>,
+		value: Box<dyn ast::Node>,
+		op: Option<Operator>,
+	) -> Self {
+		Self {
+			span,
+			target,
+			value,
+			op,
+		}
+	}
+}
+
+impl ast::Node for Assignment {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.target.define_functions(ctx)?;
+		self.value.define_functions(ctx)?;
+
+		Ok(())
+	}
+
+	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		let hlir::Node::Local(index, typ) = self.target.generate(ctx)? else {
+			return Err(Diagnostic::error("invalid left-hand side of assignment")
+				.with_code(diagnostic::INVALID_ASSIGNMENT)
+				.with_primary(self.target.span(), "cannot assign to this expression")
+				.with_note("only variables can be assigned to"));
+		};
+
+		if !ctx.is_mutable(index) {
+			return Err(Diagnostic::error("cannot assign to an immutable variable")
+				.with_code(diagnostic::IMMUTABLE_ASSIGNMENT)
+				.with_primary(&self.span, "assignment to an immutable variable")
+				.with_secondary(ctx.definition(index), "defined here")
+				.with_note("only variables declared with `let mut` can be assigned to"));
+		}
+
+		let value = ctx.with_expected_type(Some(typ), |ctx| self.value.generate(ctx))?;
+
+		let value = match &self.op {
+			Some(op) => ast::BinaryOperation::combine(
+				op,
+				hlir::Node::Local(index, typ),
+				value,
+				&self.span,
+				self.target.span(),
+				self.value.span(),
+			)?,
+			None if value.get_type() != typ => {
+				return Err(Diagnostic::error("mismatched types")
+					.with_code(diagnostic::MISMATCHED_TYPES)
+					.with_primary(
+						self.value.span(),
+						format!("expected `{typ}`, got `{}`", value.get_type()),
+					)
+					.with_secondary(ctx.definition(index), format!("`{typ}` variable")));
+			}
+			None => value,
+		};
+
+		Ok(hlir::Node::Assignment {
+			variable: Box::new(hlir::Node::Local(index, typ)),
+			value: Box::new(value),
+		})
+	}
+}
@@ -1,59 +1,164 @@
-use crate::{ast, hlir, Location, Operator, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Operator, Result, Span};
 
 #[derive(Debug)]
 pub struct BinaryOperation {
-	location: Location,
+	span: Span,
 	lhs: Box<dyn ast::Node>,
 	rhs: Box<dyn ast::Node>,
 	op: Operator,
 }
 
 impl BinaryOperation {
-	pub fn new(
-		location: Location,
-		lhs: Box<dyn ast::Node>,
-		rhs: Box<dyn ast::Node>,
-		op: Operator,
-	) -> Self {
-		Self {
-			location,
-			lhs,
-			rhs,
-			op,
+	pub fn new(span: Span, lhs: Box<dyn ast::Node>, rhs: Box<dyn ast::Node>, op: Operator) -> Self {
+		Self { span, lhs, rhs, op }
+	}
+
+	/// Type checks `lhs op rhs` and builds the node for it. The spans are
+	/// those of the whole operation and of each operand.
+	pub fn combine(
+		op: &Operator,
+		lhs: hlir::Node,
+		rhs: hlir::Node,
+		span: &Span,
+		lhs_span: &Span,
+		rhs_span: &Span,
+	) -> Result<hlir::Node> {
+		let lhs = Box::new(lhs);
+		let rhs = Box::new(rhs);
+
+		if lhs.get_type() != rhs.get_type() {
+			Err(Diagnostic::error("mismatched types")
+				.with_code(diagnostic::MISMATCHED_TYPES)
+				.with_primary(span, "")
+				.with_secondary(lhs_span, format!("`{}`", lhs.get_type()))
+				.with_secondary(rhs_span, format!("`{}`", rhs.get_type())))?;
+		}
+
+		let typ = lhs.get_type();
+
+		let supported = match *op {
+			Operator::Equal | Operator::NotEqual => {
+				typ.is_integer() || typ == hlir::ValueType::Boolean
+			}
+			Operator::And | Operator::Or => typ == hlir::ValueType::Boolean,
+			_ => typ.is_integer(),
+		};
+
+		if !supported {
+			Err(Diagnostic::error(format!("cannot apply `{op}` to `{typ}`"))
+				.with_code(diagnostic::MISMATCHED_TYPES)
+				.with_primary(span, format!("`{typ}` does not support `{op}`")))?;
 		}
+
+		Ok(match op {
+			Operator::Add => hlir::Node::Add { lhs, rhs },
+			Operator::Sub => hlir::Node::Sub { lhs, rhs },
+			Operator::Mul => hlir::Node::Mul { lhs, rhs },
+			Operator::Div => hlir::Node::Div { lhs, rhs },
+			Operator::Mod => hlir::Node::Mod { lhs, rhs },
+			Operator::Equal => hlir::Node::Equals { lhs, rhs },
+			Operator::NotEqual => hlir::Node::NotEquals { lhs, rhs },
+			Operator::Less => hlir::Node::Less { lhs, rhs },
+			Operator::LessEqual => hlir::Node::LessEquals { lhs, rhs },
+			Operator::Greater => hlir::Node::Greater { lhs, rhs },
+			Operator::GreaterEqual => hlir::Node::GreaterEquals { lhs, rhs },
+			Operator::And => hlir::Node::And { lhs, rhs },
+			Operator::Or => hlir::Node::Or { lhs, rhs },
+			Operator::Negate | Operator::Not => unreachable!(),
+		})
 	}
 }
 
 impl ast::Node for BinaryOperation {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
-		self.lhs.define_functions(ctx);
-		self.rhs.define_functions(ctx);
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.lhs.define_functions(ctx)?;
+		self.rhs.define_functions(ctx)?;
+
+		Ok(())
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
-		let lhs = Box::new(self.lhs.generate(ctx)?);
-		let rhs = Box::new(self.rhs.generate(ctx)?);
+		// arithmetic has the type that's expected of its result, but operands of
+		// comparisons only have each other to go by
+		let expected = match self.op {
+			Operator::Add | Operator::Sub | Operator::Mul | Operator::Div | Operator::Mod => {
+				ctx.expected_type()
+			}
+			_ => None,
+		};
 
-		if lhs.get_type() != rhs.get_type() {
-			Err(format!(
-				"cannot do {:?} {:?} {:?}",
-				lhs.get_type(),
-				self.op,
-				rhs.get_type()
-			))?;
+		let (lhs, rhs) = match ctx.with_expected_type(expected, |ctx| self.lhs.generate(ctx)) {
+			Ok(mut lhs) => {
+				let rhs =
+					ctx.with_expected_type(Some(lhs.get_type()), |ctx| self.rhs.generate(ctx))?;
+
+				// lets literals on the left follow the right, as in `1 + x`
+				if lhs.get_type() != rhs.get_type() {
+					lhs =
+						ctx.with_expected_type(Some(rhs.get_type()), |ctx| self.lhs.generate(ctx))?;
+				}
+
+				(lhs, rhs)
+			}
+			// a literal on the left may only fit the type of the right, as in
+			// `18446744073709551615 + x` for a `u64`, so try that before giving up
+			Err(error) => {
+				let Ok(rhs) = ctx.with_expected_type(expected, |ctx| self.rhs.generate(ctx)) else {
+					return Err(error);
+				};
+
+				let lhs =
+					ctx.with_expected_type(Some(rhs.get_type()), |ctx| self.lhs.generate(ctx))?;
+
+				(lhs, rhs)
+			}
+		};
+
+		Self::combine(
+			&self.op,
+			lhs,
+			rhs,
+			&self.span,
+			self.lhs.span(),
+			self.rhs.span(),
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Lexer, Parser, SourceMap};
+
+	fn check(source: &str) -> crate::Result<crate::hlir::Node> {
+		let mut sources = SourceMap::new();
+		let file = sources.add("test.y", source);
+		let tokens = Lexer::new(&sources, file).lex()?;
+
+		let (ast, diagnostics) = Parser::new(tokens).parse_global_scope();
+		assert!(diagnostics.is_empty(), "{diagnostics:?}");
+
+		let mut ctx = crate::hlir::Context::new();
+		ast.define_functions(&mut ctx)?;
+		ast.generate(&mut ctx)
+	}
+
+	#[test]
+	fn literals_follow_either_operand() {
+		for expression in [
+			"x + 18446744073709551615",
+			"18446744073709551615 + x",
+			"x == 18446744073709551615",
+			"18446744073709551615 == x",
+		] {
+			let source = format!("fn f(x: u64) -> bool {{ let y = {expression}; return true; }}");
+			assert!(check(&source).is_ok(), "{expression}");
 		}
 
-		Ok(match self.op {
-			Operator::Add => hlir::Node::Add { lhs, rhs },
-			Operator::Sub => todo!(),
-			Operator::Mul => hlir::Node::Mul { lhs, rhs },
-			Operator::Div => todo!(),
-			Operator::Assignment => hlir::Node::Assignment {
-				variable: lhs,
-				value: rhs,
-			},
-			Operator::Equal => hlir::Node::Equals { lhs, rhs },
-			Operator::NotEqual => todo!(),
-		})
+		assert!(check("fn f(x: u8) -> bool { return 256 == x; }").is_err());
+		assert!(check("fn f() -> bool { return 18446744073709551615 == 1; }").is_err());
 	}
 }
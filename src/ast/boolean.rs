@@ -0,0 +1,27 @@
+use crate::{ast, hlir, Result, Span};
+
+#[derive(Debug)]
+pub struct Boolean {
+	span: Span,
+	value: bool,
+}
+
+impl Boolean {
+	pub fn new(span: Span, value: bool) -> Self {
+		Self { span, value }
+	}
+}
+
+impl ast::Node for Boolean {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, _ctx: &mut hlir::Context) -> Result<()> {
+		Ok(())
+	}
+
+	fn generate(&self, _ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		Ok(hlir::Node::Boolean(self.value))
+	}
+}
@@ -1,20 +1,20 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
 
 #[derive(Debug)]
 pub struct Call {
-	location: Location,
+	span: Span,
 	function: Box<dyn ast::Node>,
 	arguments: Vec<Box<dyn ast::Node>>,
 }
 
 impl Call {
 	pub fn new(
-		location: Location,
+		span: Span,
 		function: Box<dyn ast::Node>,
 		arguments: Vec<Box<dyn ast::Node>>,
 	) -> Self {
 		Self {
-			location,
+			span,
 			function,
 			arguments,
 		}
@@ -22,26 +22,75 @@ impl Call {
 }
 
 impl ast::Node for Call {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
-		self.function.define_functions(ctx);
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.function.define_functions(ctx)?;
 
 		for i in &self.arguments {
-			i.define_functions(ctx);
+			i.define_functions(ctx)?;
 		}
+
+		Ok(())
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
-		let function = self.function.generate(ctx)?;
+		let function = self.function.generate_callee(ctx)?;
+
+		let signature = match &function {
+			hlir::Node::Function(index) => ctx.signature(*index).clone(),
+			hlir::Node::Builtin(builtin) => builtin.signature(),
+			_ => {
+				return Err(Diagnostic::error("only functions can be called")
+					.with_code(diagnostic::NOT_CALLABLE)
+					.with_primary(
+						self.function.span(),
+						format!("this is `{}`", function.get_type()),
+					))
+			}
+		};
 
 		let arguments = self
 			.arguments
 			.iter()
-			.map(|x| x.generate(ctx))
+			.enumerate()
+			.map(|(index, x)| {
+				let expected = signature.parameters.get(index).copied();
+				ctx.with_expected_type(expected, |ctx| x.generate(ctx))
+			})
 			.collect::<Result<Vec<_>>>()?;
 
+		if arguments.len() != signature.parameters.len() {
+			Err(Diagnostic::error(format!(
+				"expected {} argument(s), got {}",
+				signature.parameters.len(),
+				arguments.len()
+			))
+			.with_code(diagnostic::WRONG_ARGUMENT_COUNT)
+			.with_primary(&self.span, ""))?;
+		}
+
+		for ((argument, node), parameter) in arguments
+			.iter()
+			.zip(&self.arguments)
+			.zip(&signature.parameters)
+		{
+			if argument.get_type() != *parameter {
+				Err(Diagnostic::error("mismatched types")
+					.with_code(diagnostic::MISMATCHED_TYPES)
+					.with_primary(
+						node.span(),
+						format!("expected `{parameter}`, got `{}`", argument.get_type()),
+					))?;
+			}
+		}
+
 		Ok(hlir::Node::Call {
 			function: Box::new(function),
 			arguments,
+			result: signature.result,
 		})
 	}
 }
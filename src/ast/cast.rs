@@ -0,0 +1,57 @@
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
+
+#[derive(Debug)]
+pub struct Cast {
+	span: Span,
+	value: Box<dyn ast::Node>,
+	typ: String,
+	typ_span: Span,
+}
+
+impl Cast {
+	pub fn new(span: Span, value: Box<dyn ast::Node>, typ: String, typ_span: Span) -> Self {
+		Self {
+			span,
+			value,
+			typ,
+			typ_span,
+		}
+	}
+}
+
+impl ast::Node for Cast {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.value.define_functions(ctx)?;
+
+		Ok(())
+	}
+
+	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		let typ = ctx.resolve_type(&self.typ, &self.typ_span)?;
+		let value = ctx.with_expected_type(None, |ctx| self.value.generate(ctx))?;
+		let from = value.get_type();
+
+		// integers convert between each other by truncating or extending, and
+		// `bool` becomes 0 or 1
+		let valid = from == typ
+			|| typ.is_integer() && (from.is_integer() || from == hlir::ValueType::Boolean);
+
+		if !valid {
+			Err(
+				Diagnostic::error(format!("cannot cast `{from}` as `{typ}`"))
+					.with_code(diagnostic::INVALID_CAST)
+					.with_primary(&self.span, "invalid cast")
+					.with_note("only integers and `bool` can be cast, and only to integers"),
+			)?;
+		}
+
+		Ok(hlir::Node::Cast {
+			value: Box::new(value),
+			typ,
+		})
+	}
+}
@@ -0,0 +1,32 @@
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
+
+#[derive(Debug)]
+pub struct Continue {
+	span: Span,
+}
+
+impl Continue {
+	pub fn new(span: Span) -> Self {
+		Self { span }
+	}
+}
+
+impl ast::Node for Continue {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, _ctx: &mut hlir::Context) -> Result<()> {
+		Ok(())
+	}
+
+	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		if !ctx.is_in_loop() {
+			Err(Diagnostic::error("`continue` outside of a loop")
+				.with_code(diagnostic::OUTSIDE_OF_LOOP)
+				.with_primary(&self.span, "cannot `continue` outside of a loop"))?;
+		}
+
+		Ok(hlir::Node::Continue)
+	}
+}
@@ -1,24 +1,30 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{
+	ast::{self, Node},
+	diagnostic, hlir, Diagnostic, Result, Span,
+};
 
 #[derive(Debug)]
 pub struct FunctionDefinition {
-	location: Location,
+	span: Span,
+	doc: Option<String>,
 	name: String,
 	parameters: Vec<ast::ParameterDefinition>,
-	return_type: usize,
+	return_type: Option<(String, Span)>,
 	body: ast::Scope,
 }
 
 impl FunctionDefinition {
 	pub fn new(
-		location: Location,
+		span: Span,
+		doc: Option<String>,
 		name: String,
 		parameters: Vec<ast::ParameterDefinition>,
-		return_type: usize,
+		return_type: Option<(String, Span)>,
 		body: ast::Scope,
 	) -> Self {
 		Self {
-			location,
+			span,
+			doc,
 			name,
 			parameters,
 			return_type,
@@ -27,12 +33,78 @@ impl FunctionDefinition {
 	}
 }
 
+impl FunctionDefinition {
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	/// The `///` comment written above the function, if there is one.
+	pub fn doc(&self) -> Option<&str> {
+		self.doc.as_deref()
+	}
+
+	/// Checks that the function can be what a program starts at, which the
+	/// process exit code comes from.
+	pub fn check_entry_point(&self, ctx: &hlir::Context) -> Result<()> {
+		if let Some(parameter) = self.parameters.first() {
+			let span = parameter.span().to(self.parameters.last().unwrap().span());
+
+			Err(
+				Diagnostic::error(format!("`{}` can't take parameters", self.name))
+					.with_code(diagnostic::INVALID_ENTRY_POINT)
+					.with_primary(&span, "remove these"),
+			)?;
+		}
+
+		let result = self.result_type(ctx)?;
+
+		if !matches!(result, hlir::ValueType::I64 | hlir::ValueType::Unit) {
+			let (_, span) = self.return_type.as_ref().unwrap();
+
+			Err(
+				Diagnostic::error(format!("`{}` has the wrong return type", self.name))
+					.with_code(diagnostic::INVALID_ENTRY_POINT)
+					.with_primary(span, format!("expected `i64`, got `{result}`"))
+					.with_note("the entry point returns `i64` or nothing"),
+			)?;
+		}
+
+		Ok(())
+	}
+
+	fn result_type(&self, ctx: &hlir::Context) -> Result<hlir::ValueType> {
+		match &self.return_type {
+			Some((x, span)) => ctx.resolve_type(x, span),
+			None => Ok(hlir::ValueType::Unit),
+		}
+	}
+}
+
 impl ast::Node for FunctionDefinition {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
-		ctx.define_function(self.name.clone());
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		let parameters = self
+			.parameters
+			.iter()
+			.map(|x| ctx.resolve_type(x.typ(), x.span()))
+			.collect::<Result<Vec<_>>>()?;
+
+		let result = self.result_type(ctx)?;
+
+		ctx.define_function(
+			self.name.clone(),
+			hlir::Signature { parameters, result },
+			&self.span,
+		)
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		let result = self.result_type(ctx)?;
+
+		ctx.set_return_type(result);
 		ctx.push_scope();
 
 		let parameters = self
@@ -42,14 +114,28 @@ impl ast::Node for FunctionDefinition {
 			.collect::<Result<Vec<_>>>()?;
 
 		let body = self.body.generate(ctx)?;
+
+		if let Some((_, span)) = &self.return_type {
+			if result != hlir::ValueType::Unit && !body.always_returns() {
+				Err(Diagnostic::error(format!(
+					"function `{}` may not return a value on every path",
+					self.name
+				))
+				.with_code(diagnostic::MISSING_RETURN)
+				.with_primary(self.body.span(), "this can end without returning")
+				.with_secondary(span, format!("expected `{result}` because of this")))?;
+			}
+		}
+
 		let locals = ctx.local_variables().clone();
 
 		ctx.pop_scope();
 
 		Ok(hlir::Node::FunctionDefinition {
+			name: self.name.clone(),
 			body: Box::new(body),
 			parameters,
-			result: None,
+			result: Some(result).filter(|x| *x != hlir::ValueType::Unit),
 			locals,
 			is_entry_point: self.name == "main",
 		})
@@ -1,4 +1,7 @@
-use crate::{ast, hlir, Result};
+use crate::{
+	ast::{self, Node},
+	diagnostic, hlir, Diagnostic, Result,
+};
 
 #[derive(Debug)]
 pub struct GlobalScope {
@@ -15,16 +18,28 @@ impl GlobalScope {
 	pub fn push_function(&mut self, function: ast::FunctionDefinition) {
 		self.functions.push(function);
 	}
-}
 
-impl ast::Node for GlobalScope {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
+	pub fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
 		for i in &self.functions {
-			i.define_functions(ctx);
+			i.define_functions(ctx)?;
 		}
+
+		Ok(())
+	}
+
+	/// Checks that there's a `main` for the program to start at, once every
+	/// function is defined.
+	pub fn check_entry_point(&self, ctx: &hlir::Context) -> Result<()> {
+		let Some(main) = self.functions.iter().find(|x| x.name() == "main") else {
+			return Err(Diagnostic::error("`main` function not found")
+				.with_code(diagnostic::MISSING_ENTRY_POINT)
+				.with_note("programs start at `fn main() -> i64`, whose result is the exit code"));
+		};
+
+		main.check_entry_point(ctx)
 	}
 
-	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+	pub fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
 		let mut functions = Vec::new();
 
 		for i in &self.functions {
@@ -1,8 +1,8 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
 
 #[derive(Debug)]
 pub struct If {
-	location: Location,
+	span: Span,
 	condition: Box<dyn ast::Node>,
 	true_branch: ast::Scope,
 	false_branch: Option<ast::Scope>,
@@ -10,13 +10,13 @@ pub struct If {
 
 impl If {
 	pub fn new(
-		location: Location,
+		span: Span,
 		condition: Box<dyn ast::Node>,
 		true_branch: ast::Scope,
 		false_branch: Option<ast::Scope>,
 	) -> Self {
 		Self {
-			location,
+			span,
 			condition,
 			true_branch,
 			false_branch,
@@ -25,27 +25,44 @@ impl If {
 }
 
 impl ast::Node for If {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
-		self.condition.define_functions(ctx);
-		self.true_branch.define_functions(ctx);
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.condition.define_functions(ctx)?;
+		self.true_branch.define_functions(ctx)?;
 
 		if let Some(x) = &self.false_branch {
-			x.define_functions(ctx);
+			x.define_functions(ctx)?;
 		}
+
+		Ok(())
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
 		let condition = self.condition.generate(ctx)?;
 		let true_branch = self.true_branch.generate(ctx)?;
 
+		let false_branch = if let Some(x) = &self.false_branch {
+			Some(Box::new(x.generate(ctx)?))
+		} else {
+			None
+		};
+
 		if condition.get_type() != hlir::ValueType::Boolean {
-			Err(format!("expected Boolean, got {:?}", condition.get_type()))?
+			Err(Diagnostic::error("mismatched types")
+				.with_code(diagnostic::MISMATCHED_TYPES)
+				.with_primary(
+					self.condition.span(),
+					format!("expected `bool`, got `{}`", condition.get_type()),
+				))?
 		}
 
 		Ok(hlir::Node::If {
 			condition: Box::new(condition),
 			true_branch: Box::new(true_branch),
-			false_branch: None,
+			false_branch,
 		})
 	}
 }
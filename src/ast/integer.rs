@@ -1,21 +1,74 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
 
 #[derive(Debug)]
 pub struct Integer {
-	location: Location,
-	value: i64,
+	span: Span,
+	value: u64,
+	/// Set for literals written right after a `-`, so that `-128i8` fits.
+	negative: bool,
+	suffix: Option<String>,
 }
 
 impl Integer {
-	pub fn new(location: Location, value: i64) -> Self {
-		Self { location, value }
+	pub fn new(span: Span, value: u64, suffix: Option<String>) -> Self {
+		Self {
+			span,
+			value,
+			negative: false,
+			suffix,
+		}
+	}
+
+	pub fn negative(span: Span, value: u64, suffix: Option<String>) -> Self {
+		Self {
+			span,
+			value,
+			negative: true,
+			suffix,
+		}
 	}
 }
 
 impl ast::Node for Integer {
-	fn define_functions(&self, ctx: &mut hlir::Context) {}
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, _ctx: &mut hlir::Context) -> Result<()> {
+		Ok(())
+	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
-		Ok(hlir::Node::I64(self.value))
+		// without a suffix, the literal takes whatever integer type is expected
+		let typ = match &self.suffix {
+			Some(x) => ctx.resolve_type(x, &self.span)?,
+			None => ctx
+				.expected_type()
+				.filter(|x| x.is_integer())
+				.unwrap_or(hlir::ValueType::I64),
+		};
+
+		let Some((min, max)) = typ.range() else {
+			return Err(Diagnostic::error(format!("`{typ}` is not an integer type"))
+				.with_code(diagnostic::INVALID_LITERAL)
+				.with_primary(&self.span, "invalid suffix"));
+		};
+
+		let value = if self.negative {
+			-(self.value as i128)
+		} else {
+			self.value as i128
+		};
+
+		if value < min || value > max {
+			Err(
+				Diagnostic::error(format!("literal out of range for `{typ}`"))
+					.with_code(diagnostic::INVALID_LITERAL)
+					.with_primary(&self.span, format!("does not fit into `{typ}`"))
+					.with_note(format!("`{typ}` ranges from `{min}` to `{max}`")),
+			)?;
+		}
+
+		Ok(hlir::Node::Integer(value as i64, typ))
 	}
 }
@@ -1,5 +1,10 @@
+mod assignment;
 mod binary_operation;
+mod boolean;
+mod r#break;
 mod call;
+mod cast;
+mod r#continue;
 mod function_definition;
 mod global_scope;
 mod r#if;
@@ -8,18 +13,29 @@ mod node;
 mod parameter_definition;
 mod r#return;
 mod scope;
+mod string_literal;
+mod unary_operation;
 mod variable_definition;
 mod variable_lookup;
+mod r#while;
 
+pub use assignment::*;
 pub use binary_operation::*;
+pub use boolean::*;
 pub use call::*;
+pub use cast::*;
 pub use function_definition::*;
 pub use global_scope::*;
 pub use integer::*;
 pub use node::*;
 pub use parameter_definition::*;
+pub use r#break::*;
+pub use r#continue::*;
 pub use r#if::*;
 pub use r#return::*;
+pub use r#while::*;
 pub use scope::*;
+pub use string_literal::*;
+pub use unary_operation::*;
 pub use variable_definition::*;
 pub use variable_lookup::*;
@@ -1,9 +1,16 @@
-use crate::{ast, hlir, Result};
+use crate::{hlir, Result, Span};
 
 pub trait Node: std::fmt::Debug {
-	fn define_functions(&self, ctx: &mut hlir::Context);
+	fn span(&self) -> &Span;
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()>;
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node>;
 
+	/// Generates the node as the function of a call, the only place that
+	/// functions can be named.
+	fn generate_callee(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		self.generate(ctx)
+	}
+
 	fn type_check(&self) -> Result<()> {
 		Ok(())
 	}
@@ -1,27 +1,35 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, hlir, Result, Span};
 
 #[derive(Debug)]
 pub struct ParameterDefinition {
-	location: Location,
+	span: Span,
 	name: String,
 	typ: String,
 }
 
 impl ParameterDefinition {
-	pub fn new(location: Location, name: String, typ: String) -> Self {
-		Self {
-			location,
-			name,
-			typ,
-		}
+	pub fn new(span: Span, name: String, typ: String) -> Self {
+		Self { span, name, typ }
+	}
+
+	pub fn typ(&self) -> &str {
+		&self.typ
 	}
 }
 
 impl ast::Node for ParameterDefinition {
-	fn define_functions(&self, ctx: &mut hlir::Context) {}
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, _ctx: &mut hlir::Context) -> Result<()> {
+		Ok(())
+	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
-		ctx.define_variable(self.name.clone(), hlir::ValueType::I64);
-		Ok(hlir::Node::ParameterDefinition(hlir::ValueType::I64))
+		let typ = ctx.resolve_type(&self.typ, &self.span)?;
+
+		ctx.define_variable(self.name.clone(), typ, false, &self.span);
+		Ok(hlir::Node::ParameterDefinition(typ))
 	}
 }
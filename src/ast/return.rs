@@ -1,31 +1,59 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
 
 #[derive(Debug)]
 pub struct Return {
-	location: Location,
+	span: Span,
 	value: Option<Box<dyn ast::Node>>,
 }
 
 impl Return {
-	pub fn new(location: Location, value: Option<Box<dyn ast::Node>>) -> Self {
-		Self { location, value }
+	pub fn new(span: Span, value: Option<Box<dyn ast::Node>>) -> Self {
+		Self { span, value }
 	}
 }
 
 impl ast::Node for Return {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
 		if let Some(x) = &self.value {
-			x.define_functions(ctx);
+			x.define_functions(ctx)?;
 		}
+
+		Ok(())
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
 		let value = if let Some(x) = &self.value {
-			Some(Box::new(x.generate(ctx)?))
+			let expected = Some(ctx.return_type());
+			Some(Box::new(
+				ctx.with_expected_type(expected, |ctx| x.generate(ctx))?,
+			))
 		} else {
 			None
 		};
 
+		let typ = value
+			.as_ref()
+			.map_or(hlir::ValueType::Unit, |x| x.get_type());
+
+		if typ != ctx.return_type() {
+			let span = self.value.as_ref().map_or(&self.span, |x| x.span());
+
+			Err(Diagnostic::error("mismatched types")
+				.with_code(diagnostic::MISMATCHED_TYPES)
+				.with_primary(
+					span,
+					format!("expected `{}`, got `{typ}`", ctx.return_type()),
+				)
+				.with_note(format!(
+					"the function is declared to return `{}`",
+					ctx.return_type()
+				)))?;
+		}
+
 		Ok(hlir::Node::Ret { value })
 	}
 }
@@ -1,22 +1,28 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, hlir, Result, Span};
 
 #[derive(Debug)]
 pub struct Scope {
-	location: Location,
+	span: Span,
 	nodes: Vec<Box<dyn ast::Node>>,
 }
 
 impl Scope {
-	pub fn new(location: Location, nodes: Vec<Box<dyn ast::Node>>) -> Self {
-		Self { location, nodes }
+	pub fn new(span: Span, nodes: Vec<Box<dyn ast::Node>>) -> Self {
+		Self { span, nodes }
 	}
 }
 
 impl ast::Node for Scope {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
 		for i in &self.nodes {
-			i.define_functions(ctx);
+			i.define_functions(ctx)?;
 		}
+
+		Ok(())
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
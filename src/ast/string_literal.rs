@@ -0,0 +1,27 @@
+use crate::{ast, hlir, Result, Span};
+
+#[derive(Debug)]
+pub struct StringLiteral {
+	span: Span,
+	value: String,
+}
+
+impl StringLiteral {
+	pub fn new(span: Span, value: String) -> Self {
+		Self { span, value }
+	}
+}
+
+impl ast::Node for StringLiteral {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, _ctx: &mut hlir::Context) -> Result<()> {
+		Ok(())
+	}
+
+	fn generate(&self, _ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		Ok(hlir::Node::Str(self.value.clone()))
+	}
+}
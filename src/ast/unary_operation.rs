@@ -0,0 +1,53 @@
+use crate::{ast, diagnostic, hlir, Diagnostic, Operator, Result, Span};
+
+#[derive(Debug)]
+pub struct UnaryOperation {
+	span: Span,
+	value: Box<dyn ast::Node>,
+	op: Operator,
+}
+
+impl UnaryOperation {
+	pub fn new(span: Span, value: Box<dyn ast::Node>, op: Operator) -> Self {
+		Self { span, value, op }
+	}
+}
+
+impl ast::Node for UnaryOperation {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.value.define_functions(ctx)?;
+
+		Ok(())
+	}
+
+	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		let value = Box::new(self.value.generate(ctx)?);
+		let typ = value.get_type();
+
+		let (valid, expected) = match self.op {
+			Operator::Negate => (typ.is_signed(), "a signed integer"),
+			Operator::Not => (typ == hlir::ValueType::Boolean, "`bool`"),
+			_ => unreachable!(),
+		};
+
+		if !valid {
+			Err(Diagnostic::error("mismatched types")
+				.with_code(diagnostic::MISMATCHED_TYPES)
+				.with_primary(&self.span, "")
+				.with_secondary(
+					self.value.span(),
+					format!("expected {expected}, got `{typ}`"),
+				))?;
+		}
+
+		Ok(match self.op {
+			Operator::Negate => hlir::Node::Negate { value },
+			Operator::Not => hlir::Node::Not { value },
+			_ => unreachable!(),
+		})
+	}
+}
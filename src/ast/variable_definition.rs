@@ -1,33 +1,75 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
 
 #[derive(Debug)]
 pub struct VariableDefinition {
-	location: Location,
+	span: Span,
 	name: String,
+	mutable: bool,
+	/// The type written after the name, as in `let x: i64 = ...`.
+	typ: Option<(String, Span)>,
 	value: Box<dyn ast::Node>,
 }
 
 impl VariableDefinition {
-	pub fn new(location: Location, name: String, value: Box<dyn ast::Node>) -> Self {
+	pub fn new(
+		span: Span,
+		name: String,
+		mutable: bool,
+		typ: Option<(String, Span)>,
+		value: Box<dyn ast::Node>,
+	) -> Self {
 		Self {
-			location,
+			span,
 			name,
+			mutable,
+			typ,
 			value,
 		}
 	}
 }
 
 impl ast::Node for VariableDefinition {
-	fn define_functions(&self, ctx: &mut hlir::Context) {
-		self.value.define_functions(ctx);
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.value.define_functions(ctx)?;
+
+		Ok(())
 	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
-		let value = self.value.generate(ctx)?;
-		let index = ctx.define_variable(self.name.clone(), hlir::ValueType::I64);
+		let annotation = match &self.typ {
+			Some((x, span)) => Some((ctx.resolve_type(x, span)?, span)),
+			None => None,
+		};
+
+		let expected = annotation.map(|(x, _)| x);
+		let value = ctx.with_expected_type(expected, |ctx| self.value.generate(ctx))?;
+
+		// without an annotation, the variable takes the type of its value
+		let typ = match annotation {
+			Some((typ, span)) => {
+				if value.get_type() != typ {
+					Err(Diagnostic::error("mismatched types")
+						.with_code(diagnostic::MISMATCHED_TYPES)
+						.with_primary(
+							self.value.span(),
+							format!("expected `{typ}`, got `{}`", value.get_type()),
+						)
+						.with_secondary(span, "expected due to this"))?;
+				}
+
+				typ
+			}
+			None => value.get_type(),
+		};
+
+		let index = ctx.define_variable(self.name.clone(), typ, self.mutable, &self.span);
 
 		Ok(hlir::Node::Assignment {
-			variable: Box::new(hlir::Node::Local(index)),
+			variable: Box::new(hlir::Node::Local(index, typ)),
 			value: Box::new(value),
 		})
 	}
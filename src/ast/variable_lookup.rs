@@ -1,30 +1,52 @@
-use crate::{ast, hlir, Location, Result};
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
 
 #[derive(Debug)]
 pub struct VariableLookup {
-	location: Location,
+	span: Span,
 	identifier: String,
 }
 
 impl VariableLookup {
-	pub fn new(location: Location, identifier: String) -> Self {
-		Self {
-			location,
-			identifier,
-		}
+	pub fn new(span: Span, identifier: String) -> Self {
+		Self { span, identifier }
 	}
 }
 
 impl ast::Node for VariableLookup {
-	fn define_functions(&self, ctx: &mut hlir::Context) {}
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, _ctx: &mut hlir::Context) -> Result<()> {
+		Ok(())
+	}
 
 	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
-		if let Some((index, _)) = ctx.find_variable(&self.identifier) {
-			Ok(hlir::Node::Local(index))
+		match self.generate_callee(ctx)? {
+			hlir::Node::Function(_) | hlir::Node::Builtin(_) => Err(Diagnostic::error(format!(
+				"expected value, found function `{}`",
+				self.identifier
+			))
+			.with_code(diagnostic::NOT_A_VALUE)
+			.with_primary(&self.span, "not a value")
+			.with_note("functions can only be called")),
+			x => Ok(x),
+		}
+	}
+
+	fn generate_callee(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		if let Some((index, typ)) = ctx.find_variable(&self.identifier) {
+			Ok(hlir::Node::Local(index, typ))
 		} else if let Some(index) = ctx.find_function(&self.identifier) {
 			Ok(hlir::Node::Function(index))
+		} else if let Some(builtin) = ctx.find_builtin(&self.identifier) {
+			Ok(hlir::Node::Builtin(builtin))
 		} else {
-			Err(format!("cannot find variable '{}'", self.identifier))
+			Err(
+				Diagnostic::error(format!("cannot find `{}`", self.identifier))
+					.with_code(diagnostic::UNKNOWN_NAME)
+					.with_primary(&self.span, "not found in this scope"),
+			)
 		}
 	}
 }
@@ -0,0 +1,53 @@
+use crate::{ast, diagnostic, hlir, Diagnostic, Result, Span};
+
+#[derive(Debug)]
+pub struct While {
+	span: Span,
+	condition: Box<dyn ast::Node>,
+	body: ast::Scope,
+}
+
+impl While {
+	pub fn new(span: Span, condition: Box<dyn ast::Node>, body: ast::Scope) -> Self {
+		Self {
+			span,
+			condition,
+			body,
+		}
+	}
+}
+
+impl ast::Node for While {
+	fn span(&self) -> &Span {
+		&self.span
+	}
+
+	fn define_functions(&self, ctx: &mut hlir::Context) -> Result<()> {
+		self.condition.define_functions(ctx)?;
+		self.body.define_functions(ctx)?;
+
+		Ok(())
+	}
+
+	fn generate(&self, ctx: &mut hlir::Context) -> Result<hlir::Node> {
+		let condition = self.condition.generate(ctx)?;
+
+		if condition.get_type() != hlir::ValueType::Boolean {
+			Err(Diagnostic::error("mismatched types")
+				.with_code(diagnostic::MISMATCHED_TYPES)
+				.with_primary(
+					self.condition.span(),
+					format!("expected `bool`, got `{}`", condition.get_type()),
+				))?
+		}
+
+		ctx.push_loop();
+		let body = self.body.generate(ctx);
+		ctx.pop_loop();
+
+		Ok(hlir::Node::Loop {
+			condition: Box::new(condition),
+			body: Box::new(body?),
+		})
+	}
+}
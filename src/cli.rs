@@ -0,0 +1,159 @@
+type Result<T> = core::result::Result<T, String>;
+
+pub const USAGE: &str = "\
+usage: yuio <command> [options]
+
+commands:
+	run <file>                  compile <file> and run it in memory
+	build <file> -o <output>    compile <file> into an executable
+	build -c <file> -o <output> compile <file> into a relocatable object
+	check <file>                type check <file> without compiling it
+	emit --stage <stage> <file> print an intermediate stage of <file>
+	help                        print this message
+
+options:
+	--spill-all                 keep every value on the stack instead of
+	                            allocating registers, for debugging
+
+stages:
+	tokens, ast, hlir, llir, asm";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stage {
+	Tokens,
+	Ast,
+	Hlir,
+	Llir,
+	Asm,
+}
+
+impl Stage {
+	fn parse(name: &str) -> Result<Self> {
+		Ok(match name {
+			"tokens" => Stage::Tokens,
+			"ast" => Stage::Ast,
+			"hlir" => Stage::Hlir,
+			"llir" => Stage::Llir,
+			"asm" => Stage::Asm,
+			x => return Err(format!("unknown stage '{x}'")),
+		})
+	}
+}
+
+#[derive(Debug)]
+pub enum Command {
+	Run {
+		file: String,
+		spill_all: bool,
+	},
+	Build {
+		file: String,
+		output: String,
+		object: bool,
+		spill_all: bool,
+	},
+	Check {
+		file: String,
+	},
+	Emit {
+		file: String,
+		stage: Stage,
+		spill_all: bool,
+	},
+	Help,
+}
+
+impl Command {
+	pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+		let mut args = args.into_iter();
+
+		let Some(command) = args.next() else {
+			return Err("no command given".into());
+		};
+
+		let mut file = None;
+		let mut output = None;
+		let mut stage = None;
+		let mut object = false;
+		let mut spill_all = false;
+
+		while let Some(arg) = args.next() {
+			match arg.as_str() {
+				"-o" | "--output" => {
+					let value = args
+						.next()
+						.ok_or_else(|| format!("expected a path after '{arg}'"))?;
+
+					output = Some(value);
+				}
+				"-c" | "--object" => object = true,
+				"--spill-all" => spill_all = true,
+				"--stage" => {
+					let value = args
+						.next()
+						.ok_or_else(|| format!("expected a stage after '{arg}'"))?;
+
+					stage = Some(Stage::parse(&value)?);
+				}
+				x if x.starts_with('-') => return Err(format!("unknown option '{x}'")),
+				_ => {
+					if file.is_some() {
+						return Err(format!("unexpected argument '{arg}'"));
+					}
+
+					file = Some(arg);
+				}
+			}
+		}
+
+		let expect_file = || {
+			file.clone()
+				.ok_or_else(|| format!("'{command}' expects a file"))
+		};
+
+		let ret = match command.as_str() {
+			"run" => Command::Run {
+				file: expect_file()?,
+				spill_all: std::mem::take(&mut spill_all),
+			},
+			"build" => Command::Build {
+				file: expect_file()?,
+				output: output
+					.take()
+					.ok_or_else(|| String::from("'build' expects '-o <output>'"))?,
+				object: std::mem::take(&mut object),
+				spill_all: std::mem::take(&mut spill_all),
+			},
+			"check" => Command::Check {
+				file: expect_file()?,
+			},
+			"emit" => Command::Emit {
+				file: expect_file()?,
+				stage: stage
+					.take()
+					.ok_or_else(|| String::from("'emit' expects '--stage <stage>'"))?,
+				spill_all: std::mem::take(&mut spill_all),
+			},
+			"help" | "-h" | "--help" => Command::Help,
+			x => return Err(format!("unknown command '{x}'")),
+		};
+
+		if output.is_some() {
+			Err(format!("'{command}' does not take '-o'"))?;
+		}
+
+		if object {
+			Err(format!("'{command}' does not take '-c'"))?;
+		}
+
+		if spill_all {
+			Err(format!("'{command}' does not take '--spill-all'"))?;
+		}
+
+		if stage.is_some() {
+			Err(format!("'{command}' does not take '--stage'"))?;
+		}
+
+		Ok(ret)
+	}
+}
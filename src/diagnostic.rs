@@ -0,0 +1,195 @@
+use crate::{SourceMap, Span};
+
+pub const UNKNOWN_CHARACTER: &str = "E0001";
+pub const UNEXPECTED_TOKEN: &str = "E0002";
+pub const INVALID_LITERAL: &str = "E0003";
+pub const UNKNOWN_NAME: &str = "E0004";
+pub const UNKNOWN_TYPE: &str = "E0005";
+pub const MISMATCHED_TYPES: &str = "E0006";
+pub const WRONG_ARGUMENT_COUNT: &str = "E0007";
+pub const NOT_CALLABLE: &str = "E0008";
+pub const DUPLICATE_DEFINITION: &str = "E0009";
+pub const OUTSIDE_OF_LOOP: &str = "E0010";
+pub const UNTERMINATED_COMMENT: &str = "E0011";
+pub const UNUSED_DOC_COMMENT: &str = "E0012";
+pub const INVALID_ASSIGNMENT: &str = "E0013";
+pub const IMMUTABLE_ASSIGNMENT: &str = "E0014";
+pub const INVALID_CAST: &str = "E0015";
+pub const MISSING_ENTRY_POINT: &str = "E0016";
+pub const INVALID_ENTRY_POINT: &str = "E0017";
+pub const NOT_A_VALUE: &str = "E0018";
+pub const MISSING_RETURN: &str = "E0019";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+	Error,
+	Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Label {
+	pub span: Span,
+	pub message: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+	pub severity: Severity,
+	pub code: Option<&'static str>,
+	pub message: String,
+	// boxed to keep `Result<T, Diagnostic>` small
+	pub primary: Option<Box<Label>>,
+	pub secondary: Vec<Label>,
+	pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+	pub fn error(message: impl Into<String>) -> Self {
+		Self::new(Severity::Error, message)
+	}
+
+	pub fn warning(message: impl Into<String>) -> Self {
+		Self::new(Severity::Warning, message)
+	}
+
+	fn new(severity: Severity, message: impl Into<String>) -> Self {
+		Self {
+			severity,
+			code: None,
+			message: message.into(),
+			primary: None,
+			secondary: Vec::new(),
+			notes: Vec::new(),
+		}
+	}
+
+	pub fn with_code(mut self, code: &'static str) -> Self {
+		self.code = Some(code);
+		self
+	}
+
+	pub fn with_primary(mut self, span: &Span, message: impl Into<String>) -> Self {
+		self.primary = Some(Box::new(Label {
+			span: *span,
+			message: message.into(),
+		}));
+
+		self
+	}
+
+	pub fn with_secondary(mut self, span: &Span, message: impl Into<String>) -> Self {
+		self.secondary.push(Label {
+			span: *span,
+			message: message.into(),
+		});
+
+		self
+	}
+
+	pub fn with_note(mut self, note: impl Into<String>) -> Self {
+		self.notes.push(note.into());
+		self
+	}
+
+	/// Renders the diagnostic the way rustc does, quoting the lines of
+	/// `sources` that its labels point at.
+	pub fn render(&self, sources: &SourceMap) -> String {
+		let mut ret = match self.severity {
+			Severity::Error => String::from("error"),
+			Severity::Warning => String::from("warning"),
+		};
+
+		if let Some(code) = self.code {
+			ret += &format!("[{code}]");
+		}
+
+		ret += &format!(": {}\n", self.message);
+
+		let mut labels = self
+			.primary
+			.as_deref()
+			.into_iter()
+			.map(|x| (x, '^'))
+			.chain(self.secondary.iter().map(|x| (x, '-')))
+			.map(|(x, marker)| (x, marker, sources.start(&x.span), sources.end(&x.span)))
+			.collect::<Vec<_>>();
+
+		let first = self.primary.as_deref().or(self.secondary.first());
+		let file = first.map(|x| x.span.file);
+
+		// labels in the file being reported on come first, in source order
+		labels.sort_by_key(|(x, ..)| (Some(x.span.file) != file, x.span.file, x.span.start));
+
+		let width = labels
+			.iter()
+			.map(|(.., start, _)| start.line.to_string().len())
+			.max()
+			.unwrap_or(0);
+
+		let gutter = " ".repeat(width);
+
+		if let Some(x) = first {
+			ret += &format!("{gutter}--> {}\n", sources.start(&x.span));
+		}
+
+		if !labels.is_empty() {
+			ret += &format!("{gutter} |\n");
+		}
+
+		let mut quoted = file.map(|x| (x, 0));
+
+		for (label, marker, start, end) in labels {
+			let Some(line) = sources.line(label.span.file, start.line) else {
+				continue;
+			};
+
+			if quoted.map(|(x, _)| x) != Some(label.span.file) {
+				ret += &format!("{gutter}::: {start}\n");
+				ret += &format!("{gutter} |\n");
+			}
+
+			// labels on the same line share a single quote of it
+			if quoted != Some((label.span.file, start.line)) {
+				ret += &format!("{:>width$} | {line}\n", start.line);
+				quoted = Some((label.span.file, start.line));
+			}
+
+			let length = line.chars().count() + 1;
+
+			let last = if end.line == start.line {
+				end.column.min(length)
+			} else {
+				length
+			};
+
+			// keep tabs so that the markers line up with the quoted line
+			let padding = line
+				.chars()
+				.take(start.column - 1)
+				.map(|x| if x == '\t' { '\t' } else { ' ' })
+				.collect::<String>();
+
+			let markers = marker
+				.to_string()
+				.repeat(last.saturating_sub(start.column).max(1));
+
+			ret += &format!("{gutter} | {padding}{markers}");
+
+			if !label.message.is_empty() {
+				ret += &format!(" {}", label.message);
+			}
+
+			ret += "\n";
+		}
+
+		if !self.notes.is_empty() {
+			ret += &format!("{gutter} |\n");
+		}
+
+		for i in &self.notes {
+			ret += &format!("{gutter} = note: {i}\n");
+		}
+
+		ret
+	}
+}
@@ -0,0 +1,155 @@
+use crate::{
+	ast,
+	cli::{Command, Stage},
+	diagnostic::Severity,
+	elf, hlir, llir, x86_64, Diagnostic, Lexer, Lowerer, Parser, SourceMap, Span, Token,
+};
+
+type Result<T> = core::result::Result<T, Vec<Diagnostic>>;
+
+/// Runs `command`, printing any diagnostics it produces to stderr.
+pub fn execute(command: Command) -> std::process::ExitCode {
+	let mut sources = SourceMap::new();
+
+	match dispatch(command, &mut sources) {
+		Ok(x) => x,
+		Err(x) => {
+			report(&x, &sources);
+			std::process::ExitCode::FAILURE
+		}
+	}
+}
+
+fn dispatch(command: Command, sources: &mut SourceMap) -> Result<std::process::ExitCode> {
+	match command {
+		Command::Run { file, spill_all } => {
+			let program = compile(sources, &file, spill_all, true)?;
+			let executable = x86_64::Executable::new(&program).map_err(|x| vec![x])?;
+
+			let ret = executable.call();
+			return Ok(std::process::ExitCode::from(ret as u8));
+		}
+		Command::Build {
+			file,
+			output,
+			object: false,
+			spill_all,
+		} => {
+			let program = compile(sources, &file, spill_all, true)?;
+			let executable = elf::write_executable(&program).map_err(|x| vec![x])?;
+			write_executable(&output, &executable)?;
+		}
+		Command::Build {
+			file,
+			output,
+			object: true,
+			spill_all,
+		} => {
+			let program = compile(sources, &file, spill_all, false)?;
+			std::fs::write(&output, elf::write_object(&program))
+				.map_err(|x| vec![Diagnostic::error(format!("{output}: {x}"))])?;
+		}
+		Command::Check { file } => {
+			generate(sources, &file, false)?;
+		}
+		Command::Emit {
+			file,
+			stage,
+			spill_all,
+		} => match stage {
+			Stage::Tokens => {
+				for (token, span) in lex(sources, &file)? {
+					println!("{} {token:?}", sources.start(&span));
+				}
+			}
+			Stage::Ast => println!("{:#?}", parse(sources, &file)?),
+			Stage::Hlir => println!("{:#?}", generate(sources, &file, false)?),
+			Stage::Llir => println!("{:#?}", lower(sources, &file, false)?),
+			Stage::Asm => {
+				let program = compile(sources, &file, spill_all, false)?;
+				print!("{}", x86_64::disassemble(&program));
+			}
+		},
+		Command::Help => println!("{}", crate::cli::USAGE),
+	}
+
+	Ok(std::process::ExitCode::SUCCESS)
+}
+
+fn report(diagnostics: &[Diagnostic], sources: &SourceMap) {
+	for i in diagnostics {
+		eprint!("{}", i.render(sources));
+	}
+
+	let errors = diagnostics
+		.iter()
+		.filter(|x| x.severity == Severity::Error)
+		.count();
+
+	if errors > 1 {
+		eprintln!("error: aborting due to {errors} previous errors");
+	}
+}
+
+fn lex(sources: &mut SourceMap, path: &str) -> Result<Vec<(Token, Span)>> {
+	let file = sources.load(path).map_err(|x| vec![x])?;
+	Lexer::new(sources, file).lex().map_err(|x| vec![x])
+}
+
+fn parse(sources: &mut SourceMap, file: &str) -> Result<ast::GlobalScope> {
+	let (ast, diagnostics) = Parser::new(lex(sources, file)?).parse_global_scope();
+
+	if diagnostics.iter().any(|x| x.severity == Severity::Error) {
+		return Err(diagnostics);
+	}
+
+	report(&diagnostics, sources);
+	Ok(ast)
+}
+
+/// Type checks `file`, also requiring a `main` when `entry_point` is set,
+/// for programs that run on their own rather than being linked into others.
+fn generate(sources: &mut SourceMap, file: &str, entry_point: bool) -> Result<hlir::Node> {
+	let ast = parse(sources, file)?;
+
+	let mut ir_context = hlir::Context::new();
+	ast.define_functions(&mut ir_context)
+		.and_then(|_| {
+			if entry_point {
+				ast.check_entry_point(&ir_context)
+			} else {
+				Ok(())
+			}
+		})
+		.and_then(|_| ast.generate(&mut ir_context))
+		.map_err(|x| vec![x])
+}
+
+fn lower(sources: &mut SourceMap, file: &str, entry_point: bool) -> Result<Vec<llir::Function>> {
+	let hlir = generate(sources, file, entry_point)?;
+
+	let mut lowerer = Lowerer::new();
+	lowerer.lower(hlir);
+
+	Ok(lowerer.get())
+}
+
+fn compile(
+	sources: &mut SourceMap,
+	file: &str,
+	spill_all: bool,
+	entry_point: bool,
+) -> Result<x86_64::Program> {
+	let llir = lower(sources, file, entry_point)?;
+
+	let compiler = x86_64::Compiler::new().with_spill_all(spill_all);
+	compiler.compile(llir).map_err(|x| vec![x])
+}
+
+fn write_executable(path: &str, bytes: &[u8]) -> Result<()> {
+	use std::os::unix::fs::PermissionsExt;
+
+	std::fs::write(path, bytes)
+		.and_then(|_| std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)))
+		.map_err(|x| vec![Diagnostic::error(format!("{path}: {x}"))])
+}
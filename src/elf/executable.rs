@@ -0,0 +1,90 @@
+use crate::{
+	elf::{Header, ELF_HEADER_SIZE, PROGRAM_HEADER_SIZE},
+	x86_64::{self, Assembler, Register, SYS_EXIT},
+	Diagnostic, Result,
+};
+
+const BASE_ADDRESS: u64 = 0x400000;
+const PAGE_SIZE: u64 = 0x1000;
+
+const ET_EXEC: u16 = 2;
+const PT_LOAD: u32 = 1;
+const PF_X: u32 = 1;
+const PF_R: u32 = 4;
+
+/// Wraps compiled code into a static ELF64 executable for Linux. The code is
+/// mapped as a read/execute segment followed by a read-only one for
+/// `rodata`, and starts in a small stub that calls the entry point and exits
+/// with its return value.
+pub fn write_executable(program: &x86_64::Program) -> Result<Vec<u8>> {
+	let entry_point_offset = program.entry_point_offset.ok_or_else(|| {
+		Diagnostic::error("can't write an executable without a `main` function to start at")
+	})?;
+
+	let start = start_stub(entry_point_offset);
+
+	let code_offset = ELF_HEADER_SIZE + 2 * PROGRAM_HEADER_SIZE;
+	let text_size = code_offset + start.len() + program.code.len();
+	let entry_point = BASE_ADDRESS + code_offset as u64;
+
+	// keep the segments on separate pages so they can have separate flags
+	let rodata_offset = text_size.next_multiple_of(PAGE_SIZE as usize);
+	let file_size = rodata_offset + program.rodata.len();
+
+	let code = program.relocate(rodata_offset - code_offset - start.len());
+
+	let mut output = Vec::with_capacity(file_size);
+
+	Header {
+		typ: ET_EXEC,
+		entry_point,
+		program_header_offset: ELF_HEADER_SIZE as u64,
+		program_header_count: 2,
+		section_header_offset: 0,
+		section_header_count: 0,
+		section_name_index: 0,
+	}
+	.write(&mut output);
+
+	write_segment(&mut output, PF_R | PF_X, 0, text_size);
+	write_segment(&mut output, PF_R, rodata_offset, program.rodata.len());
+
+	assert_eq!(output.len(), code_offset);
+
+	output.extend(start);
+	output.extend(code);
+	output.resize(rodata_offset, 0);
+	output.extend(&program.rodata);
+
+	Ok(output)
+}
+
+/// Writes a `PT_LOAD` program header that maps `size` bytes of the file at
+/// `offset` to the same offset from `BASE_ADDRESS`.
+fn write_segment(output: &mut Vec<u8>, flags: u32, offset: usize, size: usize) {
+	let address = BASE_ADDRESS + offset as u64;
+
+	output.extend(PT_LOAD.to_le_bytes());
+	output.extend(flags.to_le_bytes());
+	output.extend((offset as u64).to_le_bytes()); // p_offset
+	output.extend(address.to_le_bytes()); // p_vaddr
+	output.extend(address.to_le_bytes()); // p_paddr
+	output.extend((size as u64).to_le_bytes()); // p_filesz
+	output.extend((size as u64).to_le_bytes()); // p_memsz
+	output.extend(PAGE_SIZE.to_le_bytes()); // p_align
+}
+
+/// Calls the entry point, `entry_point_offset` bytes into the code that
+/// follows the stub, and exits with its return value.
+fn start_stub(entry_point_offset: usize) -> Vec<u8> {
+	let mut assembler = Assembler::new();
+	let entry_point = assembler.label();
+
+	assembler.call(entry_point);
+	assembler.mov(Register::Rdi, Register::Rax);
+	assembler.mov(Register::Rax, i64::from(SYS_EXIT));
+	assembler.syscall();
+
+	assembler.bind_at(entry_point, assembler.position() + entry_point_offset);
+	assembler.finish()
+}
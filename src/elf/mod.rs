@@ -0,0 +1,43 @@
+mod executable;
+mod object;
+
+pub use executable::*;
+pub use object::*;
+
+const ELF_HEADER_SIZE: usize = 64;
+const PROGRAM_HEADER_SIZE: usize = 56;
+const SECTION_HEADER_SIZE: usize = 64;
+
+const EM_X86_64: u16 = 62;
+
+struct Header {
+	typ: u16,
+	entry_point: u64,
+	program_header_offset: u64,
+	program_header_count: u16,
+	section_header_offset: u64,
+	section_header_count: u16,
+	section_name_index: u16,
+}
+
+impl Header {
+	fn write(&self, output: &mut Vec<u8>) {
+		// e_ident: magic, 64-bit, little endian, version 1, System V ABI
+		output.extend([0x7F, b'E', b'L', b'F', 2, 1, 1, 0]);
+		output.extend([0; 8]);
+
+		output.extend(self.typ.to_le_bytes());
+		output.extend(EM_X86_64.to_le_bytes());
+		output.extend(1u32.to_le_bytes()); // e_version
+		output.extend(self.entry_point.to_le_bytes());
+		output.extend(self.program_header_offset.to_le_bytes());
+		output.extend(self.section_header_offset.to_le_bytes());
+		output.extend(0u32.to_le_bytes()); // e_flags
+		output.extend((ELF_HEADER_SIZE as u16).to_le_bytes());
+		output.extend((PROGRAM_HEADER_SIZE as u16).to_le_bytes());
+		output.extend(self.program_header_count.to_le_bytes());
+		output.extend((SECTION_HEADER_SIZE as u16).to_le_bytes());
+		output.extend(self.section_header_count.to_le_bytes());
+		output.extend(self.section_name_index.to_le_bytes());
+	}
+}
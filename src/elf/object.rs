@@ -0,0 +1,248 @@
+use crate::{
+	elf::{Header, ELF_HEADER_SIZE},
+	x86_64,
+};
+
+const ET_REL: u16 = 1;
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_INFO_LINK: u64 = 0x40;
+
+const STB_LOCAL: u8 = 0;
+const STB_GLOBAL: u8 = 1;
+const STT_FUNC: u8 = 2;
+const STT_SECTION: u8 = 3;
+
+const R_X86_64_PC32: u64 = 2;
+const R_X86_64_PLT32: u64 = 4;
+
+const SYMBOL_SIZE: u64 = 24;
+const RELOCATION_SIZE: u64 = 24;
+
+const TEXT_INDEX: u16 = 1;
+const RODATA_INDEX: u16 = 2;
+const SYMTAB_INDEX: u32 = 3;
+const STRTAB_INDEX: u32 = 4;
+const SHSTRTAB_INDEX: u16 = 7;
+
+/// Index of the `.rodata` section symbol.
+const RODATA_SYMBOL: u64 = 2;
+
+/// Index of the first function symbol, after the null symbol and the section
+/// symbols.
+const FIRST_GLOBAL: usize = 3;
+
+struct Section {
+	name: &'static str,
+	typ: u32,
+	flags: u64,
+	link: u32,
+	info: u32,
+	alignment: u64,
+	entry_size: u64,
+	data: Vec<u8>,
+}
+
+/// Writes compiled code as a relocatable ELF64 object. Every function becomes
+/// a global symbol in `.text`, and every call or reference to `.rodata` gets
+/// a relocation so the object can be linked with `ld` or `cc`.
+pub fn write_object(program: &x86_64::Program) -> Vec<u8> {
+	let mut strtab = vec![0];
+	let mut symtab = Vec::new();
+
+	write_symbol(&mut symtab, 0, 0, 0, 0, 0);
+
+	for section in [TEXT_INDEX, RODATA_INDEX] {
+		write_symbol(
+			&mut symtab,
+			0,
+			(STB_LOCAL << 4) | STT_SECTION,
+			section,
+			0,
+			0,
+		);
+	}
+
+	for i in &program.symbols {
+		let name = strtab.len() as u32;
+		strtab.extend(i.name.as_bytes());
+		strtab.push(0);
+
+		write_symbol(
+			&mut symtab,
+			name,
+			(STB_GLOBAL << 4) | STT_FUNC,
+			TEXT_INDEX,
+			i.offset as u64,
+			i.size as u64,
+		);
+	}
+
+	let mut rela = Vec::new();
+
+	for (position, function) in &program.function_fixups {
+		let symbol = (FIRST_GLOBAL + function) as u64;
+
+		rela.extend((*position as u64).to_le_bytes());
+		rela.extend(((symbol << 32) | R_X86_64_PLT32).to_le_bytes());
+		rela.extend((-4i64).to_le_bytes());
+	}
+
+	for (position, offset) in &program.data_fixups {
+		rela.extend((*position as u64).to_le_bytes());
+		rela.extend(((RODATA_SYMBOL << 32) | R_X86_64_PC32).to_le_bytes());
+		rela.extend((*offset as i64 - 4).to_le_bytes());
+	}
+
+	let sections = [
+		Section {
+			name: ".text",
+			typ: SHT_PROGBITS,
+			flags: SHF_ALLOC | SHF_EXECINSTR,
+			link: 0,
+			info: 0,
+			alignment: 16,
+			entry_size: 0,
+			data: program.code.clone(),
+		},
+		Section {
+			name: ".rodata",
+			typ: SHT_PROGBITS,
+			flags: SHF_ALLOC,
+			link: 0,
+			info: 0,
+			alignment: 16,
+			entry_size: 0,
+			data: program.rodata.clone(),
+		},
+		Section {
+			name: ".symtab",
+			typ: SHT_SYMTAB,
+			flags: 0,
+			link: STRTAB_INDEX,
+			info: FIRST_GLOBAL as u32,
+			alignment: 8,
+			entry_size: SYMBOL_SIZE,
+			data: symtab,
+		},
+		Section {
+			name: ".strtab",
+			typ: SHT_STRTAB,
+			flags: 0,
+			link: 0,
+			info: 0,
+			alignment: 1,
+			entry_size: 0,
+			data: strtab,
+		},
+		Section {
+			name: ".rela.text",
+			typ: SHT_RELA,
+			flags: SHF_INFO_LINK,
+			link: SYMTAB_INDEX,
+			info: TEXT_INDEX as u32,
+			alignment: 8,
+			entry_size: RELOCATION_SIZE,
+			data: rela,
+		},
+		// marks the stack as non-executable for the linker
+		Section {
+			name: ".note.GNU-stack",
+			typ: SHT_PROGBITS,
+			flags: 0,
+			link: 0,
+			info: 0,
+			alignment: 1,
+			entry_size: 0,
+			data: Vec::new(),
+		},
+		Section {
+			name: ".shstrtab",
+			typ: SHT_STRTAB,
+			flags: 0,
+			link: 0,
+			info: 0,
+			alignment: 1,
+			entry_size: 0,
+			data: Vec::new(),
+		},
+	];
+
+	let mut shstrtab = vec![0];
+	let mut names = Vec::new();
+
+	for i in &sections {
+		names.push(shstrtab.len() as u32);
+		shstrtab.extend(i.name.as_bytes());
+		shstrtab.push(0);
+	}
+
+	let mut output = vec![0; ELF_HEADER_SIZE];
+	let mut offsets = Vec::new();
+
+	for i in &sections {
+		let data = if i.name == ".shstrtab" {
+			&shstrtab
+		} else {
+			&i.data
+		};
+
+		align(&mut output, i.alignment as usize);
+		offsets.push((output.len() as u64, data.len() as u64));
+		output.extend(data);
+	}
+
+	align(&mut output, 8);
+	let section_header_offset = output.len() as u64;
+
+	// the null section header
+	output.extend([0; 64]);
+
+	for ((i, name), (offset, size)) in sections.iter().zip(names).zip(offsets) {
+		output.extend(name.to_le_bytes());
+		output.extend(i.typ.to_le_bytes());
+		output.extend(i.flags.to_le_bytes());
+		output.extend(0u64.to_le_bytes()); // sh_addr
+		output.extend(offset.to_le_bytes());
+		output.extend(size.to_le_bytes());
+		output.extend(i.link.to_le_bytes());
+		output.extend(i.info.to_le_bytes());
+		output.extend(i.alignment.to_le_bytes());
+		output.extend(i.entry_size.to_le_bytes());
+	}
+
+	let mut header = Vec::new();
+
+	Header {
+		typ: ET_REL,
+		entry_point: 0,
+		program_header_offset: 0,
+		program_header_count: 0,
+		section_header_offset,
+		section_header_count: sections.len() as u16 + 1,
+		section_name_index: SHSTRTAB_INDEX,
+	}
+	.write(&mut header);
+
+	output[..ELF_HEADER_SIZE].copy_from_slice(&header);
+	output
+}
+
+fn write_symbol(output: &mut Vec<u8>, name: u32, info: u8, section: u16, value: u64, size: u64) {
+	output.extend(name.to_le_bytes());
+	output.push(info);
+	output.push(0); // st_other
+	output.extend(section.to_le_bytes());
+	output.extend(value.to_le_bytes());
+	output.extend(size.to_le_bytes());
+}
+
+fn align(output: &mut Vec<u8>, alignment: usize) {
+	output.resize(output.len().next_multiple_of(alignment), 0);
+}
@@ -0,0 +1,41 @@
+use crate::hlir::{self, ValueType};
+
+/// A function provided by the compiler rather than defined in the program.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Builtin {
+	/// `print(value: str)` writes `value` to stdout.
+	Print,
+	/// `print_i64(value: i64)` writes `value` and a newline to stdout.
+	PrintI64,
+	/// `write(fd: i64, buffer: str, length: i64) -> i64` writes the first
+	/// `length` bytes of `buffer` to `fd`, returning what the syscall did.
+	Write,
+	/// `exit(code: i64)` ends the process with `code`.
+	Exit,
+}
+
+impl Builtin {
+	pub fn find(name: &str) -> Option<Self> {
+		Some(match name {
+			"print" => Builtin::Print,
+			"print_i64" => Builtin::PrintI64,
+			"write" => Builtin::Write,
+			"exit" => Builtin::Exit,
+			_ => return None,
+		})
+	}
+
+	pub fn signature(&self) -> hlir::Signature {
+		let (parameters, result) = match self {
+			Builtin::Print => (vec![ValueType::Str], ValueType::Unit),
+			Builtin::PrintI64 => (vec![ValueType::I64], ValueType::Unit),
+			Builtin::Write => (
+				vec![ValueType::I64, ValueType::Str, ValueType::I64],
+				ValueType::I64,
+			),
+			Builtin::Exit => (vec![ValueType::I64], ValueType::Unit),
+		};
+
+		hlir::Signature { parameters, result }
+	}
+}
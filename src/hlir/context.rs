@@ -1,38 +1,133 @@
-use crate::hlir;
+use crate::{diagnostic, hlir, Diagnostic, Result, Span};
+
+#[derive(Debug, Clone)]
+pub struct Signature {
+	pub parameters: Vec<hlir::ValueType>,
+	pub result: hlir::ValueType,
+}
 
 #[derive(Default)]
 struct Scope {
 	variables: std::collections::HashMap<String, (usize, hlir::ValueType)>,
 }
 
+/// Where a local variable was defined, and whether it can be assigned to.
+struct Binding {
+	mutable: bool,
+	span: Span,
+}
+
 pub struct Context {
 	functions: std::collections::HashMap<String, usize>,
-	function_count: usize,
+	signatures: Vec<Signature>,
 	local_variables: Vec<hlir::ValueType>,
+	bindings: Vec<Binding>,
 	stack: Vec<Scope>,
+	loop_depth: usize,
+	return_type: hlir::ValueType,
+	/// The type the expression being generated should have, if something
+	/// around it already decided that.
+	expected_type: Option<hlir::ValueType>,
 }
 
 impl Context {
 	pub fn new() -> Self {
 		Self {
 			functions: std::collections::HashMap::new(),
-			function_count: 0,
+			signatures: Vec::new(),
 			local_variables: Vec::new(),
+			bindings: Vec::new(),
 			stack: Vec::new(),
+			loop_depth: 0,
+			return_type: hlir::ValueType::Unit,
+			expected_type: None,
 		}
 	}
 
-	pub fn define_function(&mut self, name: String) {
-		let index = self.function_count;
-		self.function_count += 1;
+	pub fn resolve_type(&self, name: &str, span: &Span) -> Result<hlir::ValueType> {
+		Ok(match name {
+			"i8" => hlir::ValueType::I8,
+			"i16" => hlir::ValueType::I16,
+			"i32" => hlir::ValueType::I32,
+			"i64" => hlir::ValueType::I64,
+			"u8" => hlir::ValueType::U8,
+			"u16" => hlir::ValueType::U16,
+			"u32" => hlir::ValueType::U32,
+			"u64" => hlir::ValueType::U64,
+			"bool" => hlir::ValueType::Boolean,
+			"str" => hlir::ValueType::Str,
+			x => {
+				return Err(Diagnostic::error(format!("unknown type `{x}`"))
+					.with_code(diagnostic::UNKNOWN_TYPE)
+					.with_primary(span, "not a type")
+					.with_note(
+						"the available types are `i8`, `i16`, `i32`, `i64`, `u8`, `u16`, \
+						 `u32`, `u64`, `bool` and `str`",
+					))
+			}
+		})
+	}
+
+	pub fn define_function(
+		&mut self,
+		name: String,
+		signature: Signature,
+		span: &Span,
+	) -> Result<()> {
+		let index = self.signatures.len();
+		self.signatures.push(signature);
+
+		if self.functions.insert(name.clone(), index).is_some() {
+			Err(
+				Diagnostic::error(format!("function `{name}` is defined more than once"))
+					.with_code(diagnostic::DUPLICATE_DEFINITION)
+					.with_primary(span, "redefined here"),
+			)?;
+		}
 
-		self.functions.insert(name, index);
+		Ok(())
 	}
 
 	pub fn find_function(&self, name: &String) -> Option<usize> {
 		self.functions.get(name).cloned()
 	}
 
+	/// Builtins come last, so that the program can shadow them with its own
+	/// functions.
+	pub fn find_builtin(&self, name: &str) -> Option<hlir::Builtin> {
+		hlir::Builtin::find(name)
+	}
+
+	pub fn signature(&self, function: usize) -> &Signature {
+		&self.signatures[function]
+	}
+
+	pub fn set_return_type(&mut self, typ: hlir::ValueType) {
+		self.return_type = typ;
+	}
+
+	pub fn return_type(&self) -> hlir::ValueType {
+		self.return_type
+	}
+
+	/// Runs `f` with `typ` as the expected type, which is what gives
+	/// unsuffixed integer literals their type.
+	pub fn with_expected_type<T>(
+		&mut self,
+		typ: Option<hlir::ValueType>,
+		f: impl FnOnce(&mut Self) -> T,
+	) -> T {
+		let previous = std::mem::replace(&mut self.expected_type, typ);
+		let ret = f(self);
+
+		self.expected_type = previous;
+		ret
+	}
+
+	pub fn expected_type(&self) -> Option<hlir::ValueType> {
+		self.expected_type
+	}
+
 	pub fn push_scope(&mut self) {
 		self.stack.push(Scope::default());
 	}
@@ -42,12 +137,35 @@ impl Context {
 
 		if self.stack.is_empty() {
 			self.local_variables.clear();
+			self.bindings.clear();
 		}
 	}
 
-	pub fn define_variable(&mut self, name: String, typ: hlir::ValueType) -> usize {
+	pub fn push_loop(&mut self) {
+		self.loop_depth += 1;
+	}
+
+	pub fn pop_loop(&mut self) {
+		self.loop_depth -= 1;
+	}
+
+	pub fn is_in_loop(&self) -> bool {
+		self.loop_depth > 0
+	}
+
+	pub fn define_variable(
+		&mut self,
+		name: String,
+		typ: hlir::ValueType,
+		mutable: bool,
+		span: &Span,
+	) -> usize {
 		let index = self.local_variables.len();
 		self.local_variables.push(typ);
+		self.bindings.push(Binding {
+			mutable,
+			span: *span,
+		});
 
 		self.stack
 			.last_mut()
@@ -68,6 +186,15 @@ impl Context {
 		None
 	}
 
+	pub fn is_mutable(&self, variable: usize) -> bool {
+		self.bindings[variable].mutable
+	}
+
+	/// Returns the span of the `let` or parameter that defined `variable`.
+	pub fn definition(&self, variable: usize) -> &Span {
+		&self.bindings[variable].span
+	}
+
 	pub fn local_variables(&self) -> &Vec<hlir::ValueType> {
 		&self.local_variables
 	}
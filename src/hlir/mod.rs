@@ -1,7 +1,9 @@
+mod builtin;
 mod context;
 mod node;
 mod value_type;
 
+pub use builtin::*;
 pub use context::*;
 pub use node::*;
 pub use value_type::*;
@@ -6,6 +6,7 @@ pub enum Node {
 		functions: Vec<Self>,
 	},
 	FunctionDefinition {
+		name: String,
 		body: Box<Self>,
 		parameters: Vec<Self>,
 		result: Option<hlir::ValueType>,
@@ -18,9 +19,16 @@ pub enum Node {
 		true_branch: Box<Self>,
 		false_branch: Option<Box<Self>>,
 	},
+	Loop {
+		condition: Box<Self>,
+		body: Box<Self>,
+	},
+	Break,
+	Continue,
 	Call {
 		function: Box<Self>,
 		arguments: Vec<Self>,
+		result: hlir::ValueType,
 	},
 	Ret {
 		value: Option<Box<Self>>,
@@ -33,58 +41,137 @@ pub enum Node {
 		lhs: Box<Self>,
 		rhs: Box<Self>,
 	},
+	Sub {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
 	Mul {
 		lhs: Box<Self>,
 		rhs: Box<Self>,
 	},
+	Div {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	Mod {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
 	Equals {
 		lhs: Box<Self>,
 		rhs: Box<Self>,
 	},
-	I64(i64),
+	NotEquals {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	Less {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	LessEquals {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	Greater {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	GreaterEquals {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	/// Only evaluates `rhs` if `lhs` is true.
+	And {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	/// Only evaluates `rhs` if `lhs` is false.
+	Or {
+		lhs: Box<Self>,
+		rhs: Box<Self>,
+	},
+	Negate {
+		value: Box<Self>,
+	},
+	Not {
+		value: Box<Self>,
+	},
+	Cast {
+		value: Box<Self>,
+		typ: hlir::ValueType,
+	},
+	/// An integer of the given type, sign or zero extended to 64 bits.
+	Integer(i64, hlir::ValueType),
+	Boolean(bool),
+	Str(String),
 	Function(usize),
-	Local(usize),
+	Builtin(hlir::Builtin),
+	Local(usize, hlir::ValueType),
 	ParameterDefinition(hlir::ValueType),
 }
 
 impl Node {
+	/// Whether running the node always ends in a `return`. Loops count as
+	/// not returning, since their condition might be false from the start.
+	pub fn always_returns(&self) -> bool {
+		match self {
+			Node::Ret { .. } => true,
+			Node::Block(x) => x.iter().any(Self::always_returns),
+			Node::If {
+				true_branch,
+				false_branch: Some(false_branch),
+				..
+			} => true_branch.always_returns() && false_branch.always_returns(),
+			_ => false,
+		}
+	}
+
 	pub fn get_type(&self) -> hlir::ValueType {
 		match self {
 			Node::GlobalScope { .. } => hlir::ValueType::Unit,
 			Node::FunctionDefinition { .. } => hlir::ValueType::Unit,
 			Node::Block(_) => hlir::ValueType::Unit,
 			Node::If { .. } => hlir::ValueType::Unit,
-			Node::Call { function, .. } => match **function {
-				Node::Function(_) => hlir::ValueType::I64,
-				_ => unreachable!(),
-			},
+			Node::Loop { .. } => hlir::ValueType::Unit,
+			Node::Break => hlir::ValueType::Unit,
+			Node::Continue => hlir::ValueType::Unit,
+			Node::Call { result, .. } => *result,
 			Node::Ret { .. } => hlir::ValueType::Unit,
 			Node::Assignment { .. } => hlir::ValueType::Unit,
-			Node::Add { lhs, rhs } => {
-				let left = lhs.get_type();
-				let right = rhs.get_type();
-
-				assert!(left == right);
-				left
-			}
-			Node::Mul { lhs, rhs } => {
+			Node::Add { lhs, rhs }
+			| Node::Sub { lhs, rhs }
+			| Node::Mul { lhs, rhs }
+			| Node::Div { lhs, rhs }
+			| Node::Mod { lhs, rhs } => {
 				let left = lhs.get_type();
 				let right = rhs.get_type();
 
 				assert!(left == right);
 				left
 			}
-			Node::Equals { lhs, rhs } => {
+			Node::Equals { lhs, rhs }
+			| Node::NotEquals { lhs, rhs }
+			| Node::Less { lhs, rhs }
+			| Node::LessEquals { lhs, rhs }
+			| Node::Greater { lhs, rhs }
+			| Node::GreaterEquals { lhs, rhs } => {
 				let left = lhs.get_type();
 				let right = rhs.get_type();
 
 				assert!(left == right);
 				hlir::ValueType::Boolean
 			}
-			Node::I64(_) => hlir::ValueType::I64,
-			Node::Function(_) => hlir::ValueType::Unit,
-			Node::Local(_) => hlir::ValueType::I64,
-			Node::ParameterDefinition(_) => hlir::ValueType::I64,
+			Node::And { .. } | Node::Or { .. } => hlir::ValueType::Boolean,
+			Node::Negate { value } => value.get_type(),
+			Node::Not { .. } => hlir::ValueType::Boolean,
+			Node::Cast { typ, .. } => *typ,
+			Node::Integer(_, typ) => *typ,
+			Node::Boolean(_) => hlir::ValueType::Boolean,
+			Node::Str(_) => hlir::ValueType::Str,
+			Node::Function(_) | Node::Builtin(_) => unreachable!("functions are only ever called"),
+			Node::Local(_, typ) => *typ,
+			Node::ParameterDefinition(typ) => *typ,
 		}
 	}
 }
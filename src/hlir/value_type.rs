@@ -1,6 +1,84 @@
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ValueType {
 	Unit,
+	I8,
+	I16,
+	I32,
 	I64,
+	U8,
+	U16,
+	U32,
+	U64,
 	Boolean,
+	/// A pointer to the bytes of a string, with its length stored in the 8
+	/// bytes right before them.
+	Str,
+}
+
+impl ValueType {
+	pub const INTEGERS: [ValueType; 8] = [
+		ValueType::I8,
+		ValueType::I16,
+		ValueType::I32,
+		ValueType::I64,
+		ValueType::U8,
+		ValueType::U16,
+		ValueType::U32,
+		ValueType::U64,
+	];
+
+	/// Size of a value in bytes, ignoring the padding it gets when stored.
+	pub fn size(&self) -> usize {
+		match self {
+			ValueType::Unit => 0,
+			ValueType::I8 | ValueType::U8 | ValueType::Boolean => 1,
+			ValueType::I16 | ValueType::U16 => 2,
+			ValueType::I32 | ValueType::U32 => 4,
+			ValueType::I64 | ValueType::U64 | ValueType::Str => 8,
+		}
+	}
+
+	pub fn is_integer(&self) -> bool {
+		Self::INTEGERS.contains(self)
+	}
+
+	pub fn is_signed(&self) -> bool {
+		matches!(
+			self,
+			ValueType::I8 | ValueType::I16 | ValueType::I32 | ValueType::I64
+		)
+	}
+
+	/// The smallest and largest values of an integer type.
+	pub fn range(&self) -> Option<(i128, i128)> {
+		if !self.is_integer() {
+			return None;
+		}
+
+		let bits = self.size() as u32 * 8;
+
+		Some(if self.is_signed() {
+			(-(1 << (bits - 1)), (1 << (bits - 1)) - 1)
+		} else {
+			(0, (1 << bits) - 1)
+		})
+	}
+}
+
+impl std::fmt::Display for ValueType {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ValueType::Unit => write!(f, "()"),
+			ValueType::I8 => write!(f, "i8"),
+			ValueType::I16 => write!(f, "i16"),
+			ValueType::I32 => write!(f, "i32"),
+			ValueType::I64 => write!(f, "i64"),
+			ValueType::U8 => write!(f, "u8"),
+			ValueType::U16 => write!(f, "u16"),
+			ValueType::U32 => write!(f, "u32"),
+			ValueType::U64 => write!(f, "u64"),
+			ValueType::Boolean => write!(f, "bool"),
+			ValueType::Str => write!(f, "str"),
+		}
+	}
 }
@@ -1,63 +1,175 @@
-use crate::{Location, Token};
-
-pub struct Lexer {
-	file_name: String,
-	line: usize,
-	column: usize,
-	file_contents: String,
-	current_index: usize,
+use crate::{diagnostic, Diagnostic, FileId, Result, SourceMap, Span, Token};
+
+pub struct Lexer<'a> {
+	file: FileId,
+	file_contents: &'a str,
+	/// Byte offset of the next character in `file_contents`.
+	position: usize,
 }
 
-impl Lexer {
-	pub fn new(file_name: impl AsRef<str>) -> Result<Self, String> {
-		let file_contents =
-			std::fs::read_to_string(file_name.as_ref()).map_err(|x| x.to_string())?;
-
-		Ok(Self {
-			file_name: String::from(file_name.as_ref()),
-			line: 1,
-			column: 1,
-			file_contents,
-			current_index: 0,
-		})
+impl<'a> Lexer<'a> {
+	pub fn new(sources: &'a SourceMap, file: FileId) -> Self {
+		Self {
+			file,
+			file_contents: sources.contents(file),
+			position: 0,
+		}
 	}
 
-	pub fn lex(mut self) -> Result<Vec<(Token, Location)>, String> {
+	pub fn lex(mut self) -> Result<Vec<(Token, Span)>> {
 		let mut ret = vec![];
 
 		loop {
-			self.skip_whitespace();
+			self.skip_trivia()?;
 
-			if self.current_index == self.file_contents.len() {
+			if self.rest().is_empty() {
 				break;
 			}
 
-			let token = match self.get_word() {
-				Some(token @ "if") => {
-					let location = self.advance(token.len());
-					Some((Token::If, location))
-				}
-				Some(token @ "fn") => {
-					let location = self.advance(token.len());
-					Some((Token::Fn, location))
-				}
-				Some(token @ "return") => {
-					let location = self.advance(token.len());
-					Some((Token::Return, location))
-				}
-				Some(token @ "let") => {
-					let location = self.advance(token.len());
-					Some((Token::Let, location))
-				}
-				Some(token) if !token.is_empty() => {
-					let token = String::from(token);
-					let location = self.advance(token.len());
-
-					if token.parse::<i64>().is_ok() {
-						Some((Token::Number(token), location))
-					} else {
-						Some((Token::Identifier(token), location))
+			if self.is_doc_comment() {
+				let mut text = String::new();
+
+				let start = self.advance_while(|c| {
+					if c == '\n' {
+						return false;
 					}
+
+					text.push(c);
+					true
+				});
+
+				// drop the slashes and the space after them
+				let text = &text[3..];
+				let text = text.strip_prefix(' ').unwrap_or(text);
+
+				let span = self.span_from(start);
+				ret.push((Token::DocComment(String::from(text)), span));
+				continue;
+			}
+
+			let token = match self.get_word() {
+				token @ "if" => {
+					let span = self.advance(token.len());
+					Some((Token::If, span))
+				}
+				token @ "else" => {
+					let span = self.advance(token.len());
+					Some((Token::Else, span))
+				}
+				token @ "while" => {
+					let span = self.advance(token.len());
+					Some((Token::While, span))
+				}
+				token @ "break" => {
+					let span = self.advance(token.len());
+					Some((Token::Break, span))
+				}
+				token @ "continue" => {
+					let span = self.advance(token.len());
+					Some((Token::Continue, span))
+				}
+				token @ "fn" => {
+					let span = self.advance(token.len());
+					Some((Token::Fn, span))
+				}
+				token @ "return" => {
+					let span = self.advance(token.len());
+					Some((Token::Return, span))
+				}
+				token @ "let" => {
+					let span = self.advance(token.len());
+					Some((Token::Let, span))
+				}
+				token @ "mut" => {
+					let span = self.advance(token.len());
+					Some((Token::Mut, span))
+				}
+				token @ "as" => {
+					let span = self.advance(token.len());
+					Some((Token::As, span))
+				}
+				token @ "true" => {
+					let span = self.advance(token.len());
+					Some((Token::True, span))
+				}
+				token @ "false" => {
+					let span = self.advance(token.len());
+					Some((Token::False, span))
+				}
+				token if token.starts_with(|x: char| x.is_ascii_digit()) => {
+					let span = self.advance(token.len());
+					Some((Self::lex_integer(token, &span)?, span))
+				}
+				token if !token.is_empty() => {
+					let span = self.advance(token.len());
+					Some((Token::Identifier(String::from(token)), span))
+				}
+				_ => None,
+			};
+
+			if let Some(token) = token {
+				ret.push(token);
+				continue;
+			}
+
+			if self.get_char(0) == Some('\'') {
+				ret.push(self.lex_char()?);
+				continue;
+			}
+
+			if self.get_char(0) == Some('"') {
+				ret.push(self.lex_string()?);
+				continue;
+			}
+
+			let token = match (self.get_char(0), self.get_char(1)) {
+				(Some('='), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::EqualsEquals, span))
+				}
+				(Some('!'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::ExclamationMarkEquals, span))
+				}
+				(Some('-'), Some('>')) => {
+					let span = self.advance(2);
+					Some((Token::Arrow, span))
+				}
+				(Some('<'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::LessThanEquals, span))
+				}
+				(Some('>'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::GreaterThanEquals, span))
+				}
+				(Some('+'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::PlusEquals, span))
+				}
+				(Some('-'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::MinusEquals, span))
+				}
+				(Some('*'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::StarEquals, span))
+				}
+				(Some('/'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::SlashEquals, span))
+				}
+				(Some('%'), Some('=')) => {
+					let span = self.advance(2);
+					Some((Token::PercentEquals, span))
+				}
+				(Some('&'), Some('&')) => {
+					let span = self.advance(2);
+					Some((Token::AmpersandAmpersand, span))
+				}
+				(Some('|'), Some('|')) => {
+					let span = self.advance(2);
+					Some((Token::PipePipe, span))
 				}
 				_ => None,
 			};
@@ -69,60 +181,72 @@ impl Lexer {
 
 			let token = match self.get_char(0) {
 				Some('(') => {
-					let location = self.advance(1);
-					Some((Token::OpeningParen, location))
+					let span = self.advance(1);
+					Some((Token::OpeningParen, span))
 				}
 				Some(')') => {
-					let location = self.advance(1);
-					Some((Token::ClosingParen, location))
+					let span = self.advance(1);
+					Some((Token::ClosingParen, span))
 				}
 				Some('{') => {
-					let location = self.advance(1);
-					Some((Token::OpeningCurly, location))
+					let span = self.advance(1);
+					Some((Token::OpeningCurly, span))
 				}
 				Some('}') => {
-					let location = self.advance(1);
-					Some((Token::ClosingCurly, location))
+					let span = self.advance(1);
+					Some((Token::ClosingCurly, span))
 				}
 				Some(':') => {
-					let location = self.advance(1);
-					Some((Token::Colon, location))
+					let span = self.advance(1);
+					Some((Token::Colon, span))
 				}
 				Some(';') => {
-					let location = self.advance(1);
-					Some((Token::SemiColon, location))
+					let span = self.advance(1);
+					Some((Token::SemiColon, span))
 				}
 				Some(',') => {
-					let location = self.advance(1);
-					Some((Token::Comma, location))
+					let span = self.advance(1);
+					Some((Token::Comma, span))
 				}
 				Some('+') => {
-					let location = self.advance(1);
-					Some((Token::Plus, location))
+					let span = self.advance(1);
+					Some((Token::Plus, span))
 				}
 				Some('-') => {
-					let location = self.advance(1);
-					Some((Token::Minus, location))
+					let span = self.advance(1);
+					Some((Token::Minus, span))
 				}
 				Some('*') => {
-					let location = self.advance(1);
-					Some((Token::Star, location))
+					let span = self.advance(1);
+					Some((Token::Star, span))
 				}
 				Some('/') => {
-					let location = self.advance(1);
-					Some((Token::Slash, location))
+					let span = self.advance(1);
+					Some((Token::Slash, span))
+				}
+				Some('%') => {
+					let span = self.advance(1);
+					Some((Token::Percent, span))
+				}
+				Some('<') => {
+					let span = self.advance(1);
+					Some((Token::LessThan, span))
+				}
+				Some('>') => {
+					let span = self.advance(1);
+					Some((Token::GreaterThan, span))
 				}
 				Some('=') => {
-					let location = self.advance(1);
-					Some((Token::Equals, location))
+					let span = self.advance(1);
+					Some((Token::Equals, span))
 				}
 				Some('!') => {
-					let location = self.advance(1);
-					Some((Token::ExclamationMark, location))
+					let span = self.advance(1);
+					Some((Token::ExclamationMark, span))
 				}
 				Some('?') => {
-					let location = self.advance(1);
-					Some((Token::QuestionMark, location))
+					let span = self.advance(1);
+					Some((Token::QuestionMark, span))
 				}
 				_ => None,
 			};
@@ -132,74 +256,393 @@ impl Lexer {
 				continue;
 			}
 
-			return Err(format!(
-				"cannot lex {:?}",
-				&self.file_contents[self.current_index..]
-			))?;
+			let c = self.get_char(0).unwrap();
+			let span = self.advance(1);
+
+			return Err(Diagnostic::error(format!("unknown character {c:?}"))
+				.with_code(diagnostic::UNKNOWN_CHARACTER)
+				.with_primary(&span, ""));
 		}
 
 		Ok(ret)
 	}
 
-	fn get_while(&self, callback: impl Fn(Option<char>) -> Option<bool>) -> Option<&'_ str> {
-		let mut index = 0;
+	/// Parses an integer literal such as `1_000`, `0xFF` or `10u8`.
+	fn lex_integer(text: &str, span: &Span) -> Result<Token> {
+		let (radix, name, digits) = match text.get(..2) {
+			Some("0x") => (16, "hexadecimal", &text[2..]),
+			Some("0o") => (8, "octal", &text[2..]),
+			Some("0b") => (2, "binary", &text[2..]),
+			_ => (10, "decimal", text),
+		};
 
-		while callback(self.file_contents.chars().nth(self.current_index + index))? {
-			index += 1;
+		let end = digits
+			.find(|x: char| !x.is_digit(radix) && x != '_')
+			.unwrap_or(digits.len());
+
+		let (digits, suffix) = digits.split_at(end);
+
+		let invalid = |message: String, label: &str| {
+			Err(Diagnostic::error(message)
+				.with_code(diagnostic::INVALID_LITERAL)
+				.with_primary(span, label))
+		};
+
+		if let Some(x) = suffix.chars().next().filter(|x| x.is_ascii_digit()) {
+			return invalid(
+				format!("invalid digit `{x}` in {name} literal"),
+				"invalid digit",
+			);
 		}
 
-		Some(&self.file_contents[self.current_index..self.current_index + index])
-	}
+		let digits = digits.replace('_', "");
 
-	fn get_word(&self) -> Option<&'_ str> {
-		self.get_while(|x| Some(x?.is_ascii_alphanumeric() || x? == '_'))
-	}
+		if digits.is_empty() {
+			return invalid(format!("{name} literal has no digits"), "expected digits");
+		}
 
-	fn get_char(&self, offset: usize) -> Option<char> {
-		self.file_contents.chars().nth(self.current_index + offset)
+		let Ok(value) = u64::from_str_radix(&digits, radix) else {
+			return invalid(
+				String::from("integer literal is too large"),
+				"does not fit into 64 bits",
+			);
+		};
+
+		Ok(Token::Integer {
+			value,
+			suffix: Some(String::from(suffix)).filter(|x| !x.is_empty()),
+		})
 	}
 
-	fn advance_while(&mut self, mut callback: impl FnMut(char) -> bool) -> Location {
-		let ret = Location {
-			file_name: self.file_name.clone(),
-			line: self.line,
-			column: self.column,
+	/// Lexes a character literal such as `'a'` or `'\n'`.
+	fn lex_char(&mut self) -> Result<(Token, Span)> {
+		let start = self.advance(1);
+
+		let value = match self.get_char(0) {
+			Some('\\') => self.lex_escape()?,
+			Some('\'') => {
+				let span = start.to(&self.advance(1));
+
+				return Err(Diagnostic::error("empty character literal")
+					.with_code(diagnostic::INVALID_LITERAL)
+					.with_primary(&span, "expected a character"));
+			}
+			Some(x) if x != '\n' => {
+				self.advance(1);
+				x
+			}
+			_ => '\0',
 		};
 
+		if self.get_char(0) != Some('\'') {
+			return Err(Diagnostic::error("unterminated character literal")
+				.with_code(diagnostic::INVALID_LITERAL)
+				.with_primary(&start.to(&self.span_from(self.position)), "expected `'`"));
+		}
+
+		let end = self.advance(1);
+		Ok((Token::Char(value), start.to(&end)))
+	}
+
+	/// Lexes a string literal such as `"hello\n"`, which may span lines.
+	fn lex_string(&mut self) -> Result<(Token, Span)> {
+		let start = self.advance(1);
+		let mut value = String::new();
+
 		loop {
-			let c = self.file_contents.chars().nth(self.current_index);
+			match self.get_char(0) {
+				Some('"') => break,
+				Some('\\') => value.push(self.lex_escape()?),
+				Some(x) => {
+					self.advance(1);
+					value.push(x);
+				}
+				None => {
+					return Err(Diagnostic::error("unterminated string literal")
+						.with_code(diagnostic::INVALID_LITERAL)
+						.with_primary(&start, "string starts here"));
+				}
+			}
+		}
 
-			let Some(c) = c else {
-				break;
-			};
+		let end = self.advance(1);
+		Ok((Token::String(value), start.to(&end)))
+	}
 
-			if !callback(c) {
-				break;
+	fn lex_escape(&mut self) -> Result<char> {
+		let start = self.advance(1);
+		let escape = self.get_char(0);
+		let span = start.to(&self.advance(1));
+
+		let invalid = |message: &str, span: &Span| {
+			Diagnostic::error(message)
+				.with_code(diagnostic::INVALID_LITERAL)
+				.with_primary(span, "invalid escape")
+				.with_note("valid escapes are `\\n`, `\\r`, `\\t`, `\\0`, `\\\\`, `\\'`, `\\\"`, `\\x7F` and `\\u{7FFF}`")
+		};
+
+		Ok(match escape {
+			Some('n') => '\n',
+			Some('r') => '\r',
+			Some('t') => '\t',
+			Some('0') => '\0',
+			Some('\\') => '\\',
+			Some('\'') => '\'',
+			Some('"') => '"',
+			Some('x') => {
+				let digits = self.get_while(|x| x.is_ascii_hexdigit());
+				let digits = &digits[..digits.len().min(2)];
+				let span = span.to(&self.advance(digits.len()));
+
+				match u8::from_str_radix(digits, 16) {
+					Ok(x) if digits.len() == 2 && x <= 0x7F => char::from(x),
+					_ => {
+						return Err(invalid(
+							"`\\x` escapes take two hex digits up to `7F`",
+							&span,
+						))
+					}
+				}
 			}
+			Some('u') if self.get_char(0) == Some('{') => {
+				self.advance(1);
+
+				let digits = self.get_while(|x| x.is_ascii_hexdigit());
+				self.advance(digits.len());
 
-			if c == '\n' {
-				self.line += 1;
-				self.column = 1;
-			} else {
-				self.column += 1;
+				let closed = self.get_char(0) == Some('}');
+				let span = span.to(&self.advance(usize::from(closed)));
+
+				u32::from_str_radix(digits, 16)
+					.ok()
+					.and_then(char::from_u32)
+					.filter(|_| closed)
+					.ok_or_else(|| invalid("invalid unicode escape", &span))?
 			}
+			_ => return Err(invalid("unknown character escape", &span)),
+		})
+	}
 
-			self.current_index += 1;
-		}
+	/// The source text that hasn't been lexed yet.
+	fn rest(&self) -> &'a str {
+		&self.file_contents[self.position..]
+	}
 
-		ret
+	fn get_while(&self, mut callback: impl FnMut(char) -> bool) -> &'a str {
+		let rest = self.rest();
+
+		let end = rest
+			.char_indices()
+			.find(|(_, c)| !callback(*c))
+			.map_or(rest.len(), |(x, _)| x);
+
+		&rest[..end]
+	}
+
+	fn get_word(&self) -> &'a str {
+		self.get_while(|x| x.is_ascii_alphanumeric() || x == '_')
+	}
+
+	fn get_char(&self, offset: usize) -> Option<char> {
+		self.rest().chars().nth(offset)
 	}
 
-	fn advance(&mut self, amount: usize) -> Location {
+	fn span_from(&self, start: usize) -> Span {
+		Span::new(self.file, start as u32, self.position as u32)
+	}
+
+	/// Advances past every character that `callback` accepts, returning the
+	/// offset it started at.
+	fn advance_while(&mut self, callback: impl FnMut(char) -> bool) -> usize {
+		let start = self.position;
+
+		self.position += self.get_while(callback).len();
+		start
+	}
+
+	/// Advances past `amount` characters.
+	fn advance(&mut self, amount: usize) -> Span {
 		let mut index = 0;
 
-		self.advance_while(|_| {
+		let start = self.advance_while(|_| {
 			index += 1;
 			index <= amount
-		})
+		});
+
+		self.span_from(start)
 	}
 
 	fn skip_whitespace(&mut self) {
 		self.advance_while(|c| c.is_ascii_whitespace());
 	}
+
+	/// `///` starts a doc comment, but `////` is an ordinary comment.
+	fn is_doc_comment(&self) -> bool {
+		(0..3).all(|x| self.get_char(x) == Some('/')) && self.get_char(3) != Some('/')
+	}
+
+	/// Skips whitespace and comments, stopping in front of doc comments.
+	fn skip_trivia(&mut self) -> Result<()> {
+		loop {
+			self.skip_whitespace();
+
+			if self.is_doc_comment() {
+				return Ok(());
+			}
+
+			match (self.get_char(0), self.get_char(1)) {
+				(Some('/'), Some('/')) => {
+					self.advance_while(|c| c != '\n');
+				}
+				(Some('/'), Some('*')) => self.skip_block_comment()?,
+				_ => return Ok(()),
+			}
+		}
+	}
+
+	/// Skips a `/* */` comment, including any comments nested inside it.
+	fn skip_block_comment(&mut self) -> Result<()> {
+		let start = self.advance(2);
+		let mut depth = 1;
+
+		while depth > 0 {
+			match (self.get_char(0), self.get_char(1)) {
+				(Some('/'), Some('*')) => {
+					self.advance(2);
+					depth += 1;
+				}
+				(Some('*'), Some('/')) => {
+					self.advance(2);
+					depth -= 1;
+				}
+				(Some(_), _) => {
+					self.advance(1);
+				}
+				(None, _) => {
+					return Err(Diagnostic::error("unterminated block comment")
+						.with_code(diagnostic::UNTERMINATED_COMMENT)
+						.with_primary(&start, "comment starts here"));
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn lex(source: &str) -> Result<Vec<(Token, Span)>> {
+		let mut sources = SourceMap::new();
+		let file = sources.add("test.y", source);
+
+		Lexer::new(&sources, file).lex()
+	}
+
+	/// Builds a program of roughly `size` bytes that uses every kind of token.
+	fn synthesize(size: usize) -> String {
+		let mut ret = String::new();
+		let mut index = 0;
+
+		while ret.len() < size {
+			ret += &format!(
+				"/// function {index}\nfn f{index}(a: i64) -> i64 {{\n\t// ünïcödé\n\tlet x = a * {index} + (a - 1) / 2 % 3;\n\tif x >= 10 {{ return x; }} /* ✓ */\n\twhile x != 0 {{ break; }}\n\treturn a == x;\n}}\n\n"
+			);
+
+			index += 1;
+		}
+
+		ret
+	}
+
+	#[test]
+	fn spans_are_byte_offsets() {
+		let tokens = lex("/* é */ x").unwrap();
+
+		let (_, span) = &tokens[0];
+		assert_eq!((span.start, span.end), (9, 10));
+
+		let error = lex("x\n  ✓y").unwrap_err();
+
+		let span = &error.primary.unwrap().span;
+		assert_eq!((span.start, span.end), (4, 4 + '✓'.len_utf8() as u32));
+	}
+
+	#[test]
+	fn literals() {
+		let tokens =
+			lex(r#"0xFF 0b1010 0o17 1_000 10u8 'a' '\n' '\u{1F600}' "a\tb \"c\"\n" true false"#)
+				.unwrap()
+				.into_iter()
+				.map(|(x, _)| x)
+				.collect::<Vec<_>>();
+
+		let integer = |value, suffix: Option<&str>| Token::Integer {
+			value,
+			suffix: suffix.map(String::from),
+		};
+
+		assert_eq!(
+			tokens,
+			[
+				integer(255, None),
+				integer(10, None),
+				integer(15, None),
+				integer(1000, None),
+				integer(10, Some("u8")),
+				Token::Char('a'),
+				Token::Char('\n'),
+				Token::Char('😀'),
+				Token::String(String::from("a\tb \"c\"\n")),
+				Token::True,
+				Token::False,
+			]
+		);
+
+		assert!(lex("0b102").is_err());
+		assert!(lex("18446744073709551616").is_err());
+		assert!(lex("'ab'").is_err());
+	}
+
+	/// Lexes ever larger programs and checks that the time per byte stays
+	/// flat. Timing is too noisy to run by default, so run it with
+	/// `cargo test --release lexer -- --ignored --nocapture` to see the
+	/// throughput.
+	#[test]
+	#[ignore = "timing-based, run with --ignored"]
+	fn lexing_is_linear() {
+		let mut rates = Vec::new();
+
+		for size in [1 << 14, 1 << 16, 1 << 18] {
+			let mut sources = SourceMap::new();
+			let file = sources.add("bench.y", synthesize(size));
+			let source = sources.contents(file);
+
+			// the fastest of a few runs is the least noisy
+			let elapsed = (0..3)
+				.map(|_| {
+					let start = std::time::Instant::now();
+
+					std::hint::black_box(Lexer::new(&sources, file).lex().unwrap());
+					start.elapsed()
+				})
+				.min()
+				.unwrap();
+
+			let rate = source.len() as f64 / elapsed.as_secs_f64();
+			println!(
+				"{:>8} bytes: {elapsed:?} ({:.1} MB/s)",
+				source.len(),
+				rate / 1e6
+			);
+
+			rates.push(rate);
+		}
+
+		// a quadratic lexer slows down 16x going from the smallest to the
+		// largest input
+		assert!(rates[2] > rates[0] / 4.0, "{rates:?}");
+	}
 }
@@ -0,0 +1,137 @@
+use crate::llir;
+
+/// The range of nodes a register has to be kept alive for, from the first
+/// node that writes or needs it to the last, inclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Interval {
+	pub register: llir::Register,
+	pub start: usize,
+	pub end: usize,
+}
+
+impl Interval {
+	/// Whether the register has to survive the node at `index`, rather than
+	/// just being used or written by it.
+	pub fn spans(&self, index: usize) -> bool {
+		self.start < index && index < self.end
+	}
+}
+
+/// Computes which registers are live before every node of `function` and
+/// returns an interval covering each register that appears in it, ordered
+/// by start. Parameters are live from the first node on.
+pub fn live_intervals(function: &llir::Function) -> Vec<Interval> {
+	let body = &function.body;
+	let mut live_in = vec![std::collections::BTreeSet::new(); body.len()];
+
+	// iterate backwards until nothing changes, since loops jump back up
+	let mut changed = true;
+
+	while changed {
+		changed = false;
+
+		for (index, node) in body.iter().enumerate().rev() {
+			let mut live = node
+				.successors(index)
+				.into_iter()
+				.filter_map(|x| live_in.get(x))
+				.flatten()
+				.copied()
+				.collect::<std::collections::BTreeSet<_>>();
+
+			if let Some(x) = node.definition() {
+				live.remove(&x);
+			}
+
+			live.extend(node.uses());
+
+			if live != live_in[index] {
+				live_in[index] = live;
+				changed = true;
+			}
+		}
+	}
+
+	let mut ranges = std::collections::BTreeMap::<llir::Register, (usize, usize)>::new();
+
+	let mut extend = |register, index| {
+		let range = ranges.entry(register).or_insert((index, index));
+
+		range.0 = range.0.min(index);
+		range.1 = range.1.max(index);
+	};
+
+	for index in 0..function.parameters.len() {
+		extend(llir::Register(index), 0);
+	}
+
+	for (index, node) in body.iter().enumerate() {
+		for register in live_in[index].iter().copied().chain(node.definition()) {
+			extend(register, index);
+		}
+	}
+
+	let mut ret = ranges
+		.into_iter()
+		.map(|(register, (start, end))| Interval {
+			register,
+			start,
+			end,
+		})
+		.collect::<Vec<_>>();
+
+	ret.sort_by_key(|x| x.start);
+	ret
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use llir::{Node, Register};
+
+	fn interval(intervals: &[Interval], register: usize) -> (usize, usize) {
+		let x = intervals
+			.iter()
+			.find(|x| x.register == Register(register))
+			.unwrap();
+
+		(x.start, x.end)
+	}
+
+	#[test]
+	fn registers_stay_live_around_loops() {
+		// counts `r0` down by `r1`, which is last read at 3 but needed again
+		// every time the jump at 4 goes back to the top of the loop
+		let mut function = llir::Function::new("f".into(), false, Vec::new());
+		function.register_count = 2;
+		function.body = vec![
+			Node::MoveImmI64 {
+				dst: Register(0),
+				imm: 5,
+			},
+			Node::MoveImmI64 {
+				dst: Register(1),
+				imm: 1,
+			},
+			Node::JumpOnZero {
+				condition: Register(0),
+				target: 5,
+			},
+			Node::Sub {
+				dst: Register(0),
+				lhs: Register(0),
+				rhs: Register(1),
+			},
+			Node::Jump { target: 2 },
+			Node::Return {
+				value: Some(Register(0)),
+			},
+		];
+
+		let intervals = live_intervals(&function);
+
+		assert_eq!(interval(&intervals, 0), (0, 5));
+		assert_eq!(interval(&intervals, 1), (1, 4));
+		assert!(intervals.windows(2).all(|x| x[0].start <= x[1].start));
+	}
+}
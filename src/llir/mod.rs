@@ -1,7 +1,12 @@
+mod liveness;
+
+pub use liveness::*;
+
 use crate::llir;
 
 #[derive(Debug)]
 pub struct Function {
+	pub name: String,
 	pub is_entry_point: bool,
 	pub parameters: Vec<usize>,
 	pub body: Vec<llir::Node>,
@@ -9,8 +14,9 @@ pub struct Function {
 }
 
 impl Function {
-	pub fn new(is_entry_point: bool, parameters: Vec<usize>) -> Self {
+	pub fn new(name: String, is_entry_point: bool, parameters: Vec<usize>) -> Self {
 		Self {
+			name,
 			is_entry_point,
 			parameters,
 			body: Vec::new(),
@@ -19,9 +25,33 @@ impl Function {
 	}
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Register(pub usize);
 
+#[derive(Debug, Clone, Copy)]
+pub enum Comparison {
+	Equal,
+	NotEqual,
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual,
+}
+
+impl Comparison {
+	/// Returns the comparison that holds exactly when `self` doesn't.
+	pub fn negate(self) -> Self {
+		match self {
+			Comparison::Equal => Comparison::NotEqual,
+			Comparison::NotEqual => Comparison::Equal,
+			Comparison::Less => Comparison::GreaterEqual,
+			Comparison::LessEqual => Comparison::Greater,
+			Comparison::Greater => Comparison::LessEqual,
+			Comparison::GreaterEqual => Comparison::Less,
+		}
+	}
+}
+
 #[derive(Debug)]
 pub enum Node {
 	Move {
@@ -32,6 +62,11 @@ pub enum Node {
 		dst: llir::Register,
 		imm: i64,
 	},
+	/// Moves a pointer to `value`, kept in read-only data, into `dst`.
+	MoveString {
+		dst: llir::Register,
+		value: String,
+	},
 	Jump {
 		target: usize,
 	},
@@ -39,27 +74,218 @@ pub enum Node {
 		condition: llir::Register,
 		target: usize,
 	},
+	JumpOnNonZero {
+		condition: llir::Register,
+		target: usize,
+	},
+	/// Jumps to `target` unless `lhs` and `rhs` compare as `comparison` says,
+	/// without storing the result of the comparison anywhere.
+	JumpUnless {
+		comparison: llir::Comparison,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
+		target: usize,
+	},
 	Add {
 		dst: llir::Register,
 		lhs: llir::Register,
 		rhs: llir::Register,
 	},
+	Sub {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+	},
 	Mul {
 		dst: llir::Register,
 		lhs: llir::Register,
 		rhs: llir::Register,
+		signed: bool,
+	},
+	Div {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
+	},
+	Mod {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
 	},
 	Equals {
 		dst: llir::Register,
 		lhs: llir::Register,
 		rhs: llir::Register,
 	},
+	NotEquals {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+	},
+	Less {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
+	},
+	LessEquals {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
+	},
+	Greater {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
+	},
+	GreaterEquals {
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		signed: bool,
+	},
+	Negate {
+		dst: llir::Register,
+		value: llir::Register,
+	},
+	Not {
+		dst: llir::Register,
+		value: llir::Register,
+	},
+	/// Truncates `value` to its lowest `size` bytes, then sign or zero extends
+	/// it back to 64 bits.
+	Extend {
+		dst: llir::Register,
+		value: llir::Register,
+		size: usize,
+		signed: bool,
+	},
 	Return {
 		value: Option<llir::Register>,
 	},
+	/// Writes the string in `value` to stdout.
+	Print {
+		dst: llir::Register,
+		value: llir::Register,
+	},
+	/// Writes `value` in decimal and a newline to stdout.
+	PrintI64 {
+		dst: llir::Register,
+		value: llir::Register,
+	},
+	Write {
+		dst: llir::Register,
+		fd: llir::Register,
+		buffer: llir::Register,
+		length: llir::Register,
+	},
+	Exit {
+		code: llir::Register,
+	},
 	Call {
 		dst: llir::Register,
 		function: usize,
 		arguments: Vec<llir::Register>,
 	},
 }
+
+impl Node {
+	/// Registers that the node reads.
+	pub fn uses(&self) -> Vec<llir::Register> {
+		match self {
+			Node::Move { src, .. } => vec![*src],
+			Node::MoveImmI64 { .. } | Node::MoveString { .. } | Node::Jump { .. } => vec![],
+			Node::JumpOnZero { condition, .. } | Node::JumpOnNonZero { condition, .. } => {
+				vec![*condition]
+			}
+			Node::JumpUnless { lhs, rhs, .. }
+			| Node::Add { lhs, rhs, .. }
+			| Node::Sub { lhs, rhs, .. }
+			| Node::Mul { lhs, rhs, .. }
+			| Node::Div { lhs, rhs, .. }
+			| Node::Mod { lhs, rhs, .. }
+			| Node::Equals { lhs, rhs, .. }
+			| Node::NotEquals { lhs, rhs, .. }
+			| Node::Less { lhs, rhs, .. }
+			| Node::LessEquals { lhs, rhs, .. }
+			| Node::Greater { lhs, rhs, .. }
+			| Node::GreaterEquals { lhs, rhs, .. } => vec![*lhs, *rhs],
+			Node::Negate { value, .. }
+			| Node::Not { value, .. }
+			| Node::Extend { value, .. }
+			| Node::Print { value, .. }
+			| Node::PrintI64 { value, .. } => vec![*value],
+			Node::Return { value } => value.iter().copied().collect(),
+			Node::Write {
+				fd, buffer, length, ..
+			} => vec![*fd, *buffer, *length],
+			Node::Exit { code } => vec![*code],
+			Node::Call { arguments, .. } => arguments.clone(),
+		}
+	}
+
+	/// The register that the node writes, if any.
+	pub fn definition(&self) -> Option<llir::Register> {
+		match self {
+			Node::Move { dst, .. }
+			| Node::MoveImmI64 { dst, .. }
+			| Node::MoveString { dst, .. }
+			| Node::Add { dst, .. }
+			| Node::Sub { dst, .. }
+			| Node::Mul { dst, .. }
+			| Node::Div { dst, .. }
+			| Node::Mod { dst, .. }
+			| Node::Equals { dst, .. }
+			| Node::NotEquals { dst, .. }
+			| Node::Less { dst, .. }
+			| Node::LessEquals { dst, .. }
+			| Node::Greater { dst, .. }
+			| Node::GreaterEquals { dst, .. }
+			| Node::Negate { dst, .. }
+			| Node::Not { dst, .. }
+			| Node::Extend { dst, .. }
+			| Node::Print { dst, .. }
+			| Node::PrintI64 { dst, .. }
+			| Node::Write { dst, .. }
+			| Node::Call { dst, .. } => Some(*dst),
+			Node::Jump { .. }
+			| Node::JumpOnZero { .. }
+			| Node::JumpOnNonZero { .. }
+			| Node::JumpUnless { .. }
+			| Node::Return { .. }
+			| Node::Exit { .. } => None,
+		}
+	}
+
+	/// Indices of the nodes that may run after this one, which is at `index`.
+	pub fn successors(&self, index: usize) -> Vec<usize> {
+		match self {
+			Node::Jump { target } => vec![*target],
+			Node::JumpOnZero { target, .. }
+			| Node::JumpOnNonZero { target, .. }
+			| Node::JumpUnless { target, .. } => {
+				vec![index + 1, *target]
+			}
+			Node::Return { .. } | Node::Exit { .. } => vec![],
+			_ => vec![index + 1],
+		}
+	}
+
+	/// Whether the node calls a function or makes a syscall, either of which
+	/// may overwrite any register that isn't callee saved.
+	pub fn is_call(&self) -> bool {
+		matches!(
+			self,
+			Node::Call { .. }
+				| Node::Print { .. }
+				| Node::PrintI64 { .. }
+				| Node::Write { .. }
+				| Node::Exit { .. }
+		)
+	}
+}
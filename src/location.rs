@@ -1,6 +1,13 @@
+/// A position in a file, as resolved by a `SourceMap`.
 #[derive(Debug, Clone)]
 pub struct Location {
 	pub file_name: String,
 	pub line: usize,
 	pub column: usize,
 }
+
+impl std::fmt::Display for Location {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}:{}:{}", self.file_name, self.line, self.column)
+	}
+}
@@ -1,16 +1,25 @@
 use crate::{hlir, llir};
 
+#[derive(Default)]
 pub struct Lowerer {
 	functions: Vec<llir::Function>,
 	locals: std::collections::HashMap<usize, llir::Register>,
+	loops: Vec<Loop>,
+	/// Whether the function being lowered is a `main` without a result,
+	/// which still has to give the process an exit code.
+	returns_zero: bool,
+}
+
+/// Jump targets of the innermost loops being lowered. `breaks` are patched to
+/// the end of the loop once its body has been lowered.
+struct Loop {
+	start: usize,
+	breaks: Vec<usize>,
 }
 
 impl Lowerer {
 	pub fn new() -> Self {
-		Self {
-			functions: Vec::new(),
-			locals: std::collections::HashMap::new(),
-		}
+		Self::default()
 	}
 
 	pub fn lower(&mut self, node: hlir::Node) -> Option<llir::Register> {
@@ -23,42 +32,52 @@ impl Lowerer {
 				None
 			}
 			hlir::Node::FunctionDefinition {
+				name,
 				body,
 				parameters,
-				result: _,
+				result,
 				locals: _,
 				is_entry_point,
 			} => {
 				let parameter_count = parameters.len();
 
+				let types = parameters
+					.into_iter()
+					.enumerate()
+					.map(|(index, node)| {
+						let hlir::Node::ParameterDefinition(typ) = node else {
+							unreachable!();
+						};
+
+						self.locals.insert(index, llir::Register(index));
+						typ
+					})
+					.collect::<Vec<_>>();
+
 				let function = llir::Function {
+					name,
 					is_entry_point,
-					parameters: parameters
-						.into_iter()
-						.enumerate()
-						.map(|(index, node)| {
-							let hlir::Node::ParameterDefinition(typ) = node else {
-								unreachable!();
-							};
-
-							self.locals.insert(index, llir::Register(index));
-
-							match typ {
-								hlir::ValueType::Unit => 0,
-								hlir::ValueType::I64 => 8,
-								hlir::ValueType::Boolean => 1,
-							}
-						})
-						.collect::<Vec<_>>(),
+					parameters: types.iter().map(|x| x.size()).collect(),
 					body: Vec::new(),
 					register_count: parameter_count,
 				};
 
 				self.functions.push(function);
+				self.returns_zero = is_entry_point && result.is_none();
+
+				// callers following the C ABI may leave garbage above narrow
+				// arguments
+				for (index, typ) in types.into_iter().enumerate() {
+					self.extend(llir::Register(index), typ);
+				}
 
 				self.lower(*body);
 				self.locals.clear();
 
+				// functions that fall off the end still need an epilogue, and
+				// branches may target the end of the body
+				self.lower_return(None);
+
 				None
 			}
 			hlir::Node::Block(x) => {
@@ -71,30 +90,76 @@ impl Lowerer {
 			hlir::Node::If {
 				condition,
 				true_branch,
-				false_branch: _,
+				false_branch,
 			} => {
-				let condition = self.lower(*condition).unwrap();
+				let jumps = self.lower_condition(*condition, false);
+				self.lower(*true_branch);
 
-				let jmp = self.emit(llir::Node::JumpOnZero {
-					condition,
-					target: 0,
+				let skip = false_branch.map(|x| {
+					let skip = self.emit(llir::Node::Jump { target: 0 });
+					let label = self.label();
+
+					self.patch_all(jumps.clone(), label);
+					self.lower(*x);
+
+					skip
 				});
 
-				self.lower(*true_branch);
+				let label = self.label();
+
+				match skip {
+					Some(skip) => self.patch(skip, label),
+					None => self.patch_all(jumps, label),
+				}
+
+				None
+			}
+			hlir::Node::Loop { condition, body } => {
+				let start = self.label();
+				let jumps = self.lower_condition(*condition, false);
+
+				self.loops.push(Loop {
+					start,
+					breaks: Vec::new(),
+				});
+
+				self.lower(*body);
+				self.emit(llir::Node::Jump { target: start });
 
 				let label = self.label();
+				let Loop { breaks, .. } = self.loops.pop().unwrap();
 
-				if let Some(llir::Node::JumpOnZero { target, .. }) = self.get_mut(jmp) {
-					*target = label;
-				} else {
+				self.patch_all(breaks.into_iter().chain(jumps), label);
+
+				None
+			}
+			hlir::Node::Break => {
+				let jmp = self.emit(llir::Node::Jump { target: 0 });
+				self.loops.last_mut().unwrap().breaks.push(jmp);
+
+				None
+			}
+			hlir::Node::Continue => {
+				let start = self.loops.last().unwrap().start;
+				self.emit(llir::Node::Jump { target: start });
+
+				None
+			}
+			hlir::Node::Call {
+				function,
+				arguments,
+				result: _,
+			} if matches!(*function, hlir::Node::Builtin(_)) => {
+				let hlir::Node::Builtin(builtin) = *function else {
 					unreachable!();
 				};
 
-				None
+				self.lower_builtin(builtin, arguments)
 			}
 			hlir::Node::Call {
 				function,
 				arguments,
+				result: _,
 			} => {
 				let dst = self.register();
 				let node = llir::Node::Call {
@@ -113,11 +178,9 @@ impl Lowerer {
 				Some(dst)
 			}
 			hlir::Node::Ret { value } => {
-				let node = llir::Node::Return {
-					value: value.map(|x| self.lower(*x).unwrap()),
-				};
+				let value = value.map(|x| self.lower(*x).unwrap());
+				self.lower_return(value);
 
-				self.emit(node);
 				None
 			}
 			hlir::Node::Assignment { variable, value } => {
@@ -130,49 +193,223 @@ impl Lowerer {
 				None
 			}
 			hlir::Node::Add { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Add { dst, lhs, rhs });
+				self.extend(dst, typ);
+				Some(dst)
+			}
+			hlir::Node::Sub { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Sub { dst, lhs, rhs });
+				self.extend(dst, typ);
+				Some(dst)
+			}
+			hlir::Node::Mul { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Mul {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				self.extend(dst, typ);
+				Some(dst)
+			}
+			hlir::Node::Div { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Div {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				self.extend(dst, typ);
+				Some(dst)
+			}
+			hlir::Node::Mod { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Mod {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				Some(dst)
+			}
+			hlir::Node::Equals { lhs, rhs } => {
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Equals { dst, lhs, rhs });
+				Some(dst)
+			}
+			hlir::Node::NotEquals { lhs, rhs } => {
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::NotEquals { dst, lhs, rhs });
+				Some(dst)
+			}
+			hlir::Node::Less { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Less {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				Some(dst)
+			}
+			hlir::Node::LessEquals { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::LessEquals {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				Some(dst)
+			}
+			hlir::Node::Greater { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::Greater {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				Some(dst)
+			}
+			hlir::Node::GreaterEquals { lhs, rhs } => {
+				let typ = lhs.get_type();
+				let (dst, lhs, rhs) = self.lower_operands(*lhs, *rhs);
+
+				self.emit(llir::Node::GreaterEquals {
+					dst,
+					lhs,
+					rhs,
+					signed: typ.is_signed(),
+				});
+				Some(dst)
+			}
+			hlir::Node::And { lhs, rhs } => {
+				let dst = self.register();
+				let lhs = self.lower(*lhs).unwrap();
+
+				self.emit(llir::Node::Move { dst, src: lhs });
+				let jmp = self.emit(llir::Node::JumpOnZero {
+					condition: dst,
+					target: 0,
+				});
+
+				let rhs = self.lower(*rhs).unwrap();
+				self.emit(llir::Node::Move { dst, src: rhs });
+
+				let label = self.label();
+				self.patch(jmp, label);
+
+				Some(dst)
+			}
+			hlir::Node::Or { lhs, rhs } => {
 				let dst = self.register();
-				let node = llir::Node::Add {
+				let lhs = self.lower(*lhs).unwrap();
+
+				self.emit(llir::Node::Move { dst, src: lhs });
+				let jmp = self.emit(llir::Node::JumpOnZero {
+					condition: dst,
+					target: 0,
+				});
+				let skip = self.emit(llir::Node::Jump { target: 0 });
+
+				let label = self.label();
+				self.patch(jmp, label);
+
+				let rhs = self.lower(*rhs).unwrap();
+				self.emit(llir::Node::Move { dst, src: rhs });
+
+				let label = self.label();
+				self.patch(skip, label);
+
+				Some(dst)
+			}
+			hlir::Node::Negate { value } => {
+				let typ = value.get_type();
+				let dst = self.register();
+				let node = llir::Node::Negate {
 					dst,
-					lhs: self.lower(*lhs).unwrap(),
-					rhs: self.lower(*rhs).unwrap(),
+					value: self.lower(*value).unwrap(),
 				};
 
 				self.emit(node);
+				self.extend(dst, typ);
 				Some(dst)
 			}
-			hlir::Node::Mul { lhs, rhs } => {
+			hlir::Node::Not { value } => {
 				let dst = self.register();
-				let node = llir::Node::Mul {
+				let node = llir::Node::Not {
 					dst,
-					lhs: self.lower(*lhs).unwrap(),
-					rhs: self.lower(*rhs).unwrap(),
+					value: self.lower(*value).unwrap(),
 				};
 
 				self.emit(node);
 				Some(dst)
 			}
-			hlir::Node::Equals { lhs, rhs } => {
+			hlir::Node::Cast { value, typ } => {
+				let value = self.lower(*value).unwrap();
 				let dst = self.register();
-				let node = llir::Node::Equals {
-					dst,
-					lhs: self.lower(*lhs).unwrap(),
-					rhs: self.lower(*rhs).unwrap(),
+
+				let node = match typ.size() {
+					1 | 2 | 4 => llir::Node::Extend {
+						dst,
+						value,
+						size: typ.size(),
+						signed: typ.is_signed(),
+					},
+					_ => llir::Node::Move { dst, src: value },
 				};
 
 				self.emit(node);
 				Some(dst)
 			}
-			hlir::Node::I64(x) => {
+			hlir::Node::Integer(x, _) => {
 				let dst = self.register();
 				let node = llir::Node::MoveImmI64 { dst, imm: x };
 
 				self.emit(node);
 				Some(dst)
 			}
-			hlir::Node::Function(_) => {
+			hlir::Node::Str(x) => {
+				let dst = self.register();
+				let node = llir::Node::MoveString { dst, value: x };
+
+				self.emit(node);
+				Some(dst)
+			}
+			hlir::Node::Boolean(x) => {
+				let dst = self.register();
+				let node = llir::Node::MoveImmI64 { dst, imm: x as i64 };
+
+				self.emit(node);
+				Some(dst)
+			}
+			hlir::Node::Function(_) | hlir::Node::Builtin(_) => {
 				unreachable!();
 			}
-			hlir::Node::Local(x) => {
+			hlir::Node::Local(x, _) => {
 				if let Some(x) = self.locals.get(&x) {
 					Some(*x)
 				} else {
@@ -188,6 +425,147 @@ impl Lowerer {
 		}
 	}
 
+	fn lower_builtin(
+		&mut self,
+		builtin: hlir::Builtin,
+		arguments: Vec<hlir::Node>,
+	) -> Option<llir::Register> {
+		let arguments = arguments
+			.into_iter()
+			.map(|x| self.lower(x).unwrap())
+			.collect::<Vec<_>>();
+
+		let dst = self.register();
+
+		let node = match (builtin, arguments.as_slice()) {
+			(hlir::Builtin::Print, [value]) => llir::Node::Print { dst, value: *value },
+			(hlir::Builtin::PrintI64, [value]) => llir::Node::PrintI64 { dst, value: *value },
+			(hlir::Builtin::Write, [fd, buffer, length]) => llir::Node::Write {
+				dst,
+				fd: *fd,
+				buffer: *buffer,
+				length: *length,
+			},
+			(hlir::Builtin::Exit, [code]) => llir::Node::Exit { code: *code },
+			_ => unreachable!(),
+		};
+
+		self.emit(node);
+		Some(dst)
+	}
+
+	/// Lowers `condition` into jumps taken when it's `jump_if`, returning them
+	/// so they can be patched once the caller knows where that is. `&&`, `||`,
+	/// `!` and comparisons become branches without ever storing a boolean.
+	fn lower_condition(&mut self, condition: hlir::Node, jump_if: bool) -> Vec<usize> {
+		let comparison = match condition {
+			hlir::Node::And { lhs, rhs } if !jump_if => {
+				let mut jumps = self.lower_condition(*lhs, false);
+				jumps.extend(self.lower_condition(*rhs, false));
+
+				return jumps;
+			}
+			hlir::Node::Or { lhs, rhs } if jump_if => {
+				let mut jumps = self.lower_condition(*lhs, true);
+				jumps.extend(self.lower_condition(*rhs, true));
+
+				return jumps;
+			}
+			// `rhs` decides unless `lhs` already has, in which case skip it
+			hlir::Node::And { lhs, rhs } | hlir::Node::Or { lhs, rhs } => {
+				let skips = self.lower_condition(*lhs, !jump_if);
+				let jumps = self.lower_condition(*rhs, jump_if);
+
+				let label = self.label();
+				self.patch_all(skips, label);
+
+				return jumps;
+			}
+			hlir::Node::Not { value } => return self.lower_condition(*value, !jump_if),
+			hlir::Node::Equals { lhs, rhs } => (llir::Comparison::Equal, lhs, rhs),
+			hlir::Node::NotEquals { lhs, rhs } => (llir::Comparison::NotEqual, lhs, rhs),
+			hlir::Node::Less { lhs, rhs } => (llir::Comparison::Less, lhs, rhs),
+			hlir::Node::LessEquals { lhs, rhs } => (llir::Comparison::LessEqual, lhs, rhs),
+			hlir::Node::Greater { lhs, rhs } => (llir::Comparison::Greater, lhs, rhs),
+			hlir::Node::GreaterEquals { lhs, rhs } => (llir::Comparison::GreaterEqual, lhs, rhs),
+			condition => {
+				let condition = self.lower(condition).unwrap();
+
+				let node = if jump_if {
+					llir::Node::JumpOnNonZero {
+						condition,
+						target: 0,
+					}
+				} else {
+					llir::Node::JumpOnZero {
+						condition,
+						target: 0,
+					}
+				};
+
+				return vec![self.emit(node)];
+			}
+		};
+
+		let (comparison, lhs, rhs) = comparison;
+		let signed = lhs.get_type().is_signed();
+
+		// jumping unless the opposite holds is jumping when it does
+		let comparison = if jump_if {
+			comparison.negate()
+		} else {
+			comparison
+		};
+
+		let node = llir::Node::JumpUnless {
+			comparison,
+			lhs: self.lower(*lhs).unwrap(),
+			rhs: self.lower(*rhs).unwrap(),
+			signed,
+			target: 0,
+		};
+
+		vec![self.emit(node)]
+	}
+
+	/// Values narrower than 64 bits are kept sign or zero extended in their
+	/// registers, so this redoes that for results that may have wrapped.
+	fn extend(&mut self, register: llir::Register, typ: hlir::ValueType) {
+		if let 1 | 2 | 4 = typ.size() {
+			self.emit(llir::Node::Extend {
+				dst: register,
+				value: register,
+				size: typ.size(),
+				signed: typ.is_signed(),
+			});
+		}
+	}
+
+	fn lower_operands(
+		&mut self,
+		lhs: hlir::Node,
+		rhs: hlir::Node,
+	) -> (llir::Register, llir::Register, llir::Register) {
+		let dst = self.register();
+		let lhs = self.lower(lhs).unwrap();
+		let rhs = self.lower(rhs).unwrap();
+
+		(dst, lhs, rhs)
+	}
+
+	fn lower_return(&mut self, value: Option<llir::Register>) {
+		let value = value.or_else(|| {
+			self.returns_zero.then(|| {
+				let dst = self.register();
+				self.emit(llir::Node::MoveImmI64 { dst, imm: 0 });
+
+				dst
+			})
+		});
+
+		self.emit(llir::Node::Return { value });
+	}
+
 	pub fn get(self) -> Vec<llir::Function> {
 		self.functions
 	}
@@ -218,4 +596,24 @@ impl Lowerer {
 		let func = self.functions.last_mut().unwrap();
 		func.body.get_mut(label)
 	}
+
+	fn patch(&mut self, jump: usize, label: usize) {
+		match self.get_mut(jump) {
+			Some(
+				llir::Node::Jump { target }
+				| llir::Node::JumpOnZero { target, .. }
+				| llir::Node::JumpOnNonZero { target, .. }
+				| llir::Node::JumpUnless { target, .. },
+			) => {
+				*target = label;
+			}
+			_ => unreachable!(),
+		}
+	}
+
+	fn patch_all(&mut self, jumps: impl IntoIterator<Item = usize>, label: usize) {
+		for i in jumps {
+			self.patch(i, label);
+		}
+	}
 }
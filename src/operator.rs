@@ -4,21 +4,60 @@ pub enum Operator {
 	Sub,
 	Mul,
 	Div,
-	Assignment,
+	Mod,
 	Equal,
 	NotEqual,
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual,
+	And,
+	Or,
+	Negate,
+	Not,
 }
 
 impl Operator {
 	pub fn precedence(&self) -> u8 {
 		match *self {
-			Operator::Add => 3,
-			Operator::Sub => 3,
-			Operator::Mul => 4,
-			Operator::Div => 4,
-			Operator::Assignment => 1,
-			Operator::Equal => 2,
-			Operator::NotEqual => 2,
+			Operator::Add => 4,
+			Operator::Sub => 4,
+			Operator::Mul => 5,
+			Operator::Div => 5,
+			Operator::Mod => 5,
+			Operator::Equal => 3,
+			Operator::NotEqual => 3,
+			Operator::Less => 3,
+			Operator::LessEqual => 3,
+			Operator::Greater => 3,
+			Operator::GreaterEqual => 3,
+			Operator::And => 2,
+			Operator::Or => 1,
+			Operator::Negate => 6,
+			Operator::Not => 6,
 		}
 	}
 }
+
+impl std::fmt::Display for Operator {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let text = match self {
+			Operator::Add => "+",
+			Operator::Sub | Operator::Negate => "-",
+			Operator::Mul => "*",
+			Operator::Div => "/",
+			Operator::Mod => "%",
+			Operator::Equal => "==",
+			Operator::NotEqual => "!=",
+			Operator::Less => "<",
+			Operator::LessEqual => "<=",
+			Operator::Greater => ">",
+			Operator::GreaterEqual => ">=",
+			Operator::And => "&&",
+			Operator::Or => "||",
+			Operator::Not => "!",
+		};
+
+		write!(f, "{text}")
+	}
+}
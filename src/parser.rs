@@ -1,242 +1,410 @@
-use crate::{ast, Location, Operator, Result, Token};
+use crate::{
+	ast::{self, Node},
+	diagnostic, Diagnostic, Operator, Result, Span, Token,
+};
 
 pub struct Parser {
-	tokens: std::collections::VecDeque<(Token, Location)>,
+	tokens: std::collections::VecDeque<(Token, Span)>,
+	/// For parsers over part of the input, the token right after that part,
+	/// which is what's found once `tokens` runs out.
+	next: Option<(Token, Span)>,
+	/// Where the input ends, for reporting missing tokens there.
+	end: Option<Span>,
+	diagnostics: Vec<Diagnostic>,
+}
+
+/// A balanced `open ... close` group popped by `pop_scope`.
+struct Group {
+	/// The tokens between the delimiters.
+	tokens: Vec<(Token, Span)>,
+	/// The span of the whole group.
+	span: Span,
+	close: (Token, Span),
 }
 
 impl Parser {
-	pub fn new(tokens: Vec<(Token, Location)>) -> Self {
+	pub fn new(tokens: Vec<(Token, Span)>) -> Self {
+		let end = tokens.last().map(|(_, span)| span.after());
+
 		Self {
 			tokens: tokens.into(),
+			next: None,
+			end,
+			diagnostics: Vec::new(),
 		}
 	}
 
-	pub fn parse_global_scope(mut self) -> Result<ast::GlobalScope> {
+	/// Parses every function in the file. Syntax errors don't stop the parser:
+	/// it skips the rest of a broken statement up to its `;` or `}`, or of
+	/// anything else up to the next `fn`, and keeps going, so the returned
+	/// scope holds whatever could be parsed alongside every error.
+	pub fn parse_global_scope(mut self) -> (ast::GlobalScope, Vec<Diagnostic>) {
 		let mut global_scope = ast::GlobalScope::new();
 
 		while !self.tokens.is_empty() {
-			if let Some(function) = self.try_parse_function_definition()? {
-				global_scope.push_function(function);
-				continue;
+			match self.try_parse_function_definition() {
+				Ok(Some(function)) => {
+					global_scope.push_function(function);
+					continue;
+				}
+				Ok(None) => {
+					// only a doc comment was left, which has already been warned about
+					let Some(token) = self.tokens.pop_front() else {
+						break;
+					};
+
+					let diagnostic = self.unexpected("`fn`", Some(token));
+
+					self.diagnostics.push(diagnostic);
+				}
+				Err(x) => self.diagnostics.push(x),
 			}
 
-			Err(format!(
-				"cannot parse {:?} at global scope {}",
-				self.tokens,
-				self.tokens.is_empty()
-			))?;
+			self.pop_while(|x| *x != Token::Fn);
 		}
 
-		Ok(global_scope)
+		(global_scope, self.diagnostics)
 	}
 
-	fn parse_scope(mut self) -> Result<ast::Scope> {
-		let mut nodes: Vec<Box<dyn ast::Node>> = Vec::new();
+	fn parse_scope(&mut self, span: Span) -> ast::Scope {
+		let mut nodes = Vec::new();
 
 		while !self.tokens.is_empty() {
-			if let Some(node) = self.try_parse_if()? {
-				nodes.push(Box::new(node));
+			if let Some((_, span)) = self.pop_doc_comment() {
+				self.diagnostics.push(Self::unused_doc_comment(&span));
 				continue;
 			}
 
-			if let Some(node) = self.try_parse_variable_definition()? {
-				nodes.push(Box::new(node));
-				continue;
+			match self.parse_statement() {
+				Ok(node) => nodes.push(node),
+				Err(x) => {
+					self.diagnostics.push(x);
+					self.synchronize();
+				}
 			}
+		}
 
-			if let Some(node) = self.try_parse_return()? {
-				nodes.push(Box::new(node));
-				continue;
-			}
+		ast::Scope::new(span, nodes)
+	}
 
-			let value = self.pop_while(|x| {
-				let Some(token) = x else {
-					return Err(format!("expected expression, got nothing"))?;
-				};
+	/// Parses `tokens` as the body of a block, keeping any errors in it.
+	fn parse_block(&mut self, group: Group) -> ast::Scope {
+		let mut parser = self.sub_parser(group.tokens, Some(group.close));
+		let scope = parser.parse_scope(group.span);
 
-				Ok(*token != Token::SemiColon)
-			})?;
+		self.diagnostics.append(&mut parser.diagnostics);
+		scope
+	}
 
-			let value = Self::new(value).parse_expression()?;
+	fn parse_statement(&mut self) -> Result<Box<dyn ast::Node>> {
+		if let Some(node) = self.try_parse_if()? {
+			return Ok(Box::new(node));
+		}
 
-			match self.tokens.pop_front() {
-				Some((Token::SemiColon, _)) => {}
-				x => return Err(format!("expected SemiColon, got {x:?}")),
-			}
+		if let Some(node) = self.try_parse_while()? {
+			return Ok(Box::new(node));
+		}
 
-			nodes.push(value);
+		if let Some(node) = self.try_parse_variable_definition()? {
+			return Ok(Box::new(node));
+		}
 
-			// Err(format!("cannot parse {:?} in scope", self.tokens))?;
+		if let Some(node) = self.try_parse_return()? {
+			return Ok(Box::new(node));
 		}
 
-		Ok(ast::Scope::new(
-			Location {
-				file_name: String::from(""),
-				line: 0,
-				column: 0,
-			},
-			nodes,
-		))
+		if let Some(node) = self.try_parse_loop_control()? {
+			return Ok(node);
+		}
+
+		let value = self.pop_until(Token::SemiColon)?;
+		let value = self
+			.sub_parser(value, self.next())
+			.parse_expression_statement()?;
+
+		self.expect(Token::SemiColon)?;
+
+		Ok(value)
 	}
 
-	fn parse_expression(&mut self) -> Result<Box<dyn ast::Node>> {
-		let mut value = self.parse_value()?;
-		value = self.try_parse_function_call(value)?;
+	/// Parses an expression, or an assignment like `x = ...` or `x += ...`
+	/// if one follows it.
+	fn parse_expression_statement(&mut self) -> Result<Box<dyn ast::Node>> {
+		let target = self.parse_binary(0)?;
+
+		let op = match self.tokens.front() {
+			Some((Token::Equals, _)) => None,
+			Some((Token::PlusEquals, _)) => Some(Operator::Add),
+			Some((Token::MinusEquals, _)) => Some(Operator::Sub),
+			Some((Token::StarEquals, _)) => Some(Operator::Mul),
+			Some((Token::SlashEquals, _)) => Some(Operator::Div),
+			Some((Token::PercentEquals, _)) => Some(Operator::Mod),
+			Some(_) => {
+				let token = self.tokens.pop_front();
+				return Err(self.unexpected("an operator", token));
+			}
+			None => return Ok(target),
+		};
 
-		while !self.tokens.is_empty() {
-			let Some(op) = self.parse_operator(0)? else {
-				return Err("asdfasdf").unwrap();
-			};
+		self.tokens.pop_front();
+		let value = self.parse_expression()?;
+		let span = target.span().to(value.span());
+
+		Ok(Box::new(ast::Assignment::new(span, target, value, op)))
+	}
+
+	/// Skips the rest of a broken statement: everything up to and including
+	/// the next `;`, or up to the `}` closing a block that was opened on the
+	/// way there.
+	fn synchronize(&mut self) {
+		let mut depth = 0usize;
+
+		while let Some((token, _)) = self.tokens.pop_front() {
+			match token {
+				Token::OpeningCurly => depth += 1,
+				Token::ClosingCurly if depth <= 1 => return,
+				Token::ClosingCurly => depth -= 1,
+				Token::SemiColon if depth == 0 => return,
+				_ => {}
+			}
+		}
+	}
+
+	fn parse_expression(&mut self) -> Result<Box<dyn ast::Node>> {
+		let value = self.parse_binary(0)?;
 
-			value = self.parse_operation(value, op)?;
+		if let Some(x) = self.tokens.pop_front() {
+			return Err(self.unexpected("an operator", Some(x)));
 		}
 
 		Ok(value)
 	}
 
-	fn parse_value(&mut self) -> Result<Box<dyn ast::Node>> {
-		match self.tokens.pop_front() {
-			Some((Token::Number(num), location)) => Ok(Box::new(ast::Integer::new(
-				location,
-				num.parse::<i64>().map_err(|x| x.to_string())?,
-			))),
-			Some((Token::Identifier(ident), location)) => {
-				Ok(Box::new(ast::VariableLookup::new(location, ident)))
-			}
-			x => Result::Err(format!("expr: {x:?}")).unwrap(),
+	/// Parses operands joined by operators of at least `precedence`, so that
+	/// tighter operators end up deeper in the tree.
+	fn parse_binary(&mut self, precedence: u8) -> Result<Box<dyn ast::Node>> {
+		let mut lhs = self.parse_cast()?;
+
+		while let Some(op) = self.parse_operator(precedence)? {
+			let rhs = self.parse_binary(op.precedence() + 1)?;
+			let span = lhs.span().to(rhs.span());
+
+			lhs = Box::new(ast::BinaryOperation::new(span, lhs, rhs, op));
 		}
+
+		Ok(lhs)
 	}
 
-	fn parse_operator(&mut self, precedence: u8) -> Result<Option<Operator>> {
-		let (op, size) = match self.tokens.front() {
-			Some((Token::Plus, _)) => (Operator::Add, 1),
-			Some((Token::Minus, _)) => (Operator::Sub, 1),
-			Some((Token::Star, _)) => (Operator::Mul, 1),
-			Some((Token::Slash, _)) => (Operator::Div, 1),
-			Some((Token::Equals, _)) => match self.tokens.front() {
-				Some((Token::Equals, _)) => (Operator::Equal, 2),
-				_ => (Operator::Assignment, 1),
-			},
-			Some((x @ Token::ExclamationMark, _)) => match self.tokens.front() {
-				Some((Token::Equals, _)) => (Operator::NotEqual, 2),
-				_ => Err(format!("failed to parse {x:?} into an operator"))?,
-			},
-			_ => return Ok(None),
-		};
+	/// Parses a unary expression and any `as` casts after it, which bind
+	/// tighter than every binary operator.
+	fn parse_cast(&mut self) -> Result<Box<dyn ast::Node>> {
+		let mut value = self.parse_unary()?;
 
-		Ok(if op.precedence() >= precedence {
-			for _ in 0..size {
-				self.tokens.pop_front();
-			}
+		while let Some((Token::As, _)) = self.tokens.front() {
+			self.tokens.pop_front();
 
-			Some(op)
-		} else {
-			None
-		})
+			let (typ, span) = self.expect_identifier()?;
+			let cast_span = value.span().to(&span);
+
+			value = Box::new(ast::Cast::new(cast_span, value, typ, span));
+		}
+
+		Ok(value)
 	}
 
-	fn parse_operation(
-		&mut self,
-		lhs: Box<dyn ast::Node>,
-		op: Operator,
-	) -> Result<Box<dyn ast::Node>> {
-		let mut rhs = self.parse_value()?;
+	fn parse_unary(&mut self) -> Result<Box<dyn ast::Node>> {
+		let op = match self.tokens.front() {
+			Some((Token::Minus, _)) => Operator::Negate,
+			Some((Token::ExclamationMark, _)) => Operator::Not,
+			_ => return self.parse_postfix(),
+		};
+
+		let (_, span) = self.tokens.pop_front().unwrap();
 
-		rhs = self.try_parse_function_call(rhs)?;
+		// a `-` right before a literal is part of it, so that the most
+		// negative value of each type can be written
+		if let (Operator::Negate, Some((Token::Integer { .. }, _))) = (&op, self.tokens.front()) {
+			let Some((Token::Integer { value, suffix }, end)) = self.tokens.pop_front() else {
+				unreachable!();
+			};
 
-		if let Some(x) = self.parse_operator(op.precedence())? {
-			rhs = self.parse_operation(rhs, x)?;
+			return Ok(Box::new(ast::Integer::negative(
+				span.to(&end),
+				value,
+				suffix,
+			)));
 		}
 
-		Ok(Box::new(ast::BinaryOperation::new(
-			Location {
-				file_name: "".into(),
-				line: 0,
-				column: 0,
-			},
-			lhs,
-			rhs,
+		let value = self.parse_unary()?;
+
+		Ok(Box::new(ast::UnaryOperation::new(
+			span.to(value.span()),
+			value,
 			op,
 		)))
 	}
 
-	fn parse_parameter(&mut self) -> Result<ast::ParameterDefinition> {
-		let (name, name_location) = match self.tokens.pop_front() {
-			Some((Token::Identifier(name), location)) => (name, location),
-			x => Err(format!("expected Identifier, got {x:?}"))?,
-		};
+	fn parse_postfix(&mut self) -> Result<Box<dyn ast::Node>> {
+		let mut value = self.parse_value()?;
 
+		while let Some((Token::OpeningParen, _)) = self.tokens.front() {
+			value = self.parse_function_call(value)?;
+		}
+
+		Ok(value)
+	}
+
+	fn parse_value(&mut self) -> Result<Box<dyn ast::Node>> {
 		match self.tokens.pop_front() {
-			Some((Token::Colon, _)) => {}
-			x => Err(format!("expected Colon, got {x:?}"))?,
+			Some((Token::Integer { value, suffix }, span)) => {
+				Ok(Box::new(ast::Integer::new(span, value, suffix)))
+			}
+			// characters are integers holding their code point
+			Some((Token::Char(x), span)) => Ok(Box::new(ast::Integer::new(span, x as u64, None))),
+			Some((Token::String(x), span)) => Ok(Box::new(ast::StringLiteral::new(span, x))),
+			Some((Token::True, span)) => Ok(Box::new(ast::Boolean::new(span, true))),
+			Some((Token::False, span)) => Ok(Box::new(ast::Boolean::new(span, false))),
+			Some((Token::Identifier(ident), span)) => {
+				Ok(Box::new(ast::VariableLookup::new(span, ident)))
+			}
+			Some((Token::OpeningParen, _)) => {
+				let value = self.parse_binary(0)?;
+				self.expect(Token::ClosingParen)?;
+
+				Ok(value)
+			}
+			x => Err(self.unexpected("an expression", x)),
 		}
+	}
 
-		let (typ, _) = match self.tokens.pop_front() {
-			Some((Token::Identifier(typ), location)) => (typ, location),
-			x => Err(format!("expected Identifier, got {x:?}"))?,
+	fn parse_operator(&mut self, precedence: u8) -> Result<Option<Operator>> {
+		let op = match self.tokens.front() {
+			Some((Token::Plus, _)) => Operator::Add,
+			Some((Token::Minus, _)) => Operator::Sub,
+			Some((Token::Star, _)) => Operator::Mul,
+			Some((Token::Slash, _)) => Operator::Div,
+			Some((Token::Percent, _)) => Operator::Mod,
+			Some((Token::EqualsEquals, _)) => Operator::Equal,
+			Some((Token::ExclamationMarkEquals, _)) => Operator::NotEqual,
+			Some((Token::LessThan, _)) => Operator::Less,
+			Some((Token::LessThanEquals, _)) => Operator::LessEqual,
+			Some((Token::GreaterThan, _)) => Operator::Greater,
+			Some((Token::GreaterThanEquals, _)) => Operator::GreaterEqual,
+			Some((Token::AmpersandAmpersand, _)) => Operator::And,
+			Some((Token::PipePipe, _)) => Operator::Or,
+			_ => return Ok(None),
 		};
 
-		Ok(ast::ParameterDefinition::new(name_location, name, typ))
+		if op.precedence() < precedence {
+			return Ok(None);
+		}
+
+		self.tokens.pop_front();
+		Ok(Some(op))
 	}
 
-	fn try_parse_function_call(&mut self, node: Box<dyn ast::Node>) -> Result<Box<dyn ast::Node>> {
-		let Some((Token::OpeningParen, location)) = self.tokens.front() else {
-			return Ok(node);
-		};
+	fn parse_parameter(&mut self) -> Result<ast::ParameterDefinition> {
+		let (name, name_span) = self.expect_identifier()?;
+		self.expect(Token::Colon)?;
+		let (typ, typ_span) = self.expect_identifier()?;
+
+		if let Some(x) = self.tokens.pop_front() {
+			return Err(self.unexpected("`,`", Some(x)));
+		}
+
+		Ok(ast::ParameterDefinition::new(
+			name_span.to(&typ_span),
+			name,
+			typ,
+		))
+	}
 
-		let location = location.clone();
+	fn parse_function_call(&mut self, node: Box<dyn ast::Node>) -> Result<Box<dyn ast::Node>> {
+		self.tokens.pop_front();
+		let mut arguments = Vec::new();
 
-		let arguments = self.pop_scope(Token::OpeningParen, Token::ClosingParen)?;
-		let argument = Self::new(arguments).parse_expression()?;
+		if let Some((Token::ClosingParen, _)) = self.tokens.front() {
+			let (_, end) = self.tokens.pop_front().unwrap();
+			let span = node.span().to(&end);
 
-		Ok(Box::new(ast::Call::new(location, node, vec![argument])))
+			return Ok(Box::new(ast::Call::new(span, node, arguments)));
+		}
+
+		let end = loop {
+			arguments.push(self.parse_binary(0)?);
+
+			match self.tokens.pop_front() {
+				Some((Token::Comma, _)) => {}
+				Some((Token::ClosingParen, span)) => break span,
+				x => return Err(self.unexpected("`,` or `)`", x)),
+			}
+		};
+
+		let span = node.span().to(&end);
+		Ok(Box::new(ast::Call::new(span, node, arguments)))
 	}
 
 	fn try_parse_function_definition(&mut self) -> Result<Option<ast::FunctionDefinition>> {
+		let doc = self.pop_doc_comment();
+
 		let Some((Token::Fn, _)) = self.tokens.front() else {
+			if let Some((_, span)) = doc {
+				self.diagnostics.push(Self::unused_doc_comment(&span));
+			}
+
 			return Ok(None);
 		};
 
-		let (_, location) = self.tokens.pop_front().unwrap();
+		let (_, start) = self.tokens.pop_front().unwrap();
+		let (name, _) = self.expect_identifier()?;
 
-		let name = match self.tokens.pop_front() {
-			Some((Token::Identifier(name), _)) => name,
-			x => return Err(format!("expected Identifier, got {x:?}")),
-		};
+		// an unclosed `(` stops at the body or the next function, so those can
+		// still be parsed
+		let parameters = self.pop_scope_before(
+			Token::OpeningParen,
+			Token::ClosingParen,
+			&[Token::OpeningCurly, Token::Fn],
+		)?;
+		let mut signature = parameters.span;
 
-		let parameters = self.pop_scope(Token::OpeningParen, Token::ClosingParen)?;
-		let mut parameter_parser = Self::new(parameters);
+		let mut parameter_parser = self.sub_parser(parameters.tokens, Some(parameters.close));
 		let mut parameters = Vec::new();
 
-		loop {
-			let parameter = parameter_parser.pop_while(|x| match x {
-				Some(x) => Ok(*x != Token::Comma),
-				None => Ok(false),
-			})?;
-
-			if parameter.is_empty() {
-				break;
+		while !parameter_parser.tokens.is_empty() {
+			let parameter = parameter_parser.pop_while(|x| *x != Token::Comma);
+			let next = parameter_parser.next();
+			parameters.push(
+				parameter_parser
+					.sub_parser(parameter, next)
+					.parse_parameter()?,
+			);
+
+			if !parameter_parser.tokens.is_empty() {
+				parameter_parser.expect(Token::Comma)?;
 			}
+		}
 
-			match parameter_parser.tokens.pop_front() {
-				Some((Token::Comma, _)) => {}
-				Some(x) => Err(format!("expected Comma, got {x:?}"))?,
-				_ => {}
-			}
+		let return_type = if let Some((Token::Arrow, _)) = self.tokens.front() {
+			self.tokens.pop_front();
 
-			parameters.push(Self::new(parameter).parse_parameter()?);
+			let (typ, span) = self.expect_identifier()?;
+			signature = span;
 
-			if parameter_parser.tokens.is_empty() {
-				break;
-			}
-		}
+			Some((typ, span))
+		} else {
+			None
+		};
 
 		let body = self.pop_scope(Token::OpeningCurly, Token::ClosingCurly)?;
-
-		let body = Self::new(body).parse_scope()?;
+		let body = self.parse_block(body);
 
 		Ok(Some(ast::FunctionDefinition::new(
-			location, name, parameters, 0, body,
+			start.to(&signature),
+			doc.map(|(x, _)| x),
+			name,
+			parameters,
+			return_type,
+			body,
 		)))
 	}
 
@@ -245,22 +413,71 @@ impl Parser {
 			return Ok(None);
 		};
 
-		let (_, location) = self.tokens.pop_front().unwrap();
+		let (_, start) = self.tokens.pop_front().unwrap();
 
-		let expression_tokens = self.pop_while(|x| {
-			let Some(token) = x else {
-				return Err(format!("expected expression, got nothing"))?;
-			};
+		let expression_tokens = self.pop_until(Token::OpeningCurly)?;
+		let expression = self
+			.sub_parser(expression_tokens, self.next())
+			.parse_expression()?;
+
+		let true_branch = self.pop_scope(Token::OpeningCurly, Token::ClosingCurly)?;
+		let true_branch = self.parse_block(true_branch);
+
+		let Some((Token::Else, _)) = self.tokens.front() else {
+			let span = start.to(true_branch.span());
+			return Ok(Some(ast::If::new(span, expression, true_branch, None)));
+		};
+
+		self.tokens.pop_front();
+
+		let false_branch = if let Some(node) = self.try_parse_if()? {
+			ast::Scope::new(*node.span(), vec![Box::new(node)])
+		} else {
+			let false_branch = self.pop_scope(Token::OpeningCurly, Token::ClosingCurly)?;
+			self.parse_block(false_branch)
+		};
+
+		Ok(Some(ast::If::new(
+			start.to(false_branch.span()),
+			expression,
+			true_branch,
+			Some(false_branch),
+		)))
+	}
 
-			Ok(*token != Token::OpeningCurly)
-		})?;
+	fn try_parse_while(&mut self) -> Result<Option<ast::While>> {
+		let Some((Token::While, _)) = self.tokens.front() else {
+			return Ok(None);
+		};
+
+		let (_, start) = self.tokens.pop_front().unwrap();
+
+		let expression_tokens = self.pop_until(Token::OpeningCurly)?;
+		let expression = self
+			.sub_parser(expression_tokens, self.next())
+			.parse_expression()?;
+
+		let body = self.pop_scope(Token::OpeningCurly, Token::ClosingCurly)?;
+		let body = self.parse_block(body);
 
-		let expression = Self::new(expression_tokens).parse_expression()?;
+		Ok(Some(ast::While::new(
+			start.to(body.span()),
+			expression,
+			body,
+		)))
+	}
+
+	fn try_parse_loop_control(&mut self) -> Result<Option<Box<dyn ast::Node>>> {
+		let node: Box<dyn ast::Node> = match self.tokens.front() {
+			Some((Token::Break, span)) => Box::new(ast::Break::new(*span)),
+			Some((Token::Continue, span)) => Box::new(ast::Continue::new(*span)),
+			_ => return Ok(None),
+		};
 
-		let true_branch_tokens = self.pop_scope(Token::OpeningCurly, Token::ClosingCurly)?;
-		let true_branch = Self::new(true_branch_tokens).parse_scope()?;
+		self.tokens.pop_front();
+		self.expect(Token::SemiColon)?;
 
-		Ok(Some(ast::If::new(location, expression, true_branch, None)))
+		Ok(Some(node))
 	}
 
 	fn try_parse_variable_definition(&mut self) -> Result<Option<ast::VariableDefinition>> {
@@ -268,34 +485,37 @@ impl Parser {
 			return Ok(None);
 		};
 
-		let (_, location) = self.tokens.pop_front().unwrap();
+		let (_, start) = self.tokens.pop_front().unwrap();
 
-		let name = match self.tokens.pop_front() {
-			Some((Token::Identifier(name), _)) => name,
-			x => return Err(format!("expected Identifier, got {x:?}")),
-		};
+		let mutable = matches!(self.tokens.front(), Some((Token::Mut, _)));
 
-		match self.tokens.pop_front() {
-			Some((Token::Equals, _)) => {}
-			x => return Err(format!("expected Equals, got {x:?}")),
+		if mutable {
+			self.tokens.pop_front();
 		}
 
-		let value = self.pop_while(|x| {
-			let Some(token) = x else {
-				return Err(format!("expected expression, got nothing"))?;
-			};
+		let (name, _) = self.expect_identifier()?;
 
-			Ok(*token != Token::SemiColon)
-		})?;
+		let typ = if let Some((Token::Colon, _)) = self.tokens.front() {
+			self.tokens.pop_front();
+			Some(self.expect_identifier()?)
+		} else {
+			None
+		};
 
-		let value = Self::new(value).parse_expression()?;
+		self.expect(Token::Equals)?;
 
-		match self.tokens.pop_front() {
-			Some((Token::SemiColon, _)) => {}
-			x => return Err(format!("expected SemiColon, got {x:?}")),
-		}
+		let value = self.pop_until(Token::SemiColon)?;
+		let value = self.sub_parser(value, self.next()).parse_expression()?;
+
+		let end = self.expect(Token::SemiColon)?;
 
-		Ok(Some(ast::VariableDefinition::new(location, name, value)))
+		Ok(Some(ast::VariableDefinition::new(
+			start.to(&end),
+			name,
+			mutable,
+			typ,
+			value,
+		)))
 	}
 
 	fn try_parse_return(&mut self) -> Result<Option<ast::Return>> {
@@ -303,78 +523,211 @@ impl Parser {
 			return Ok(None);
 		};
 
-		let (_, location) = self.tokens.pop_front().unwrap();
+		let (_, start) = self.tokens.pop_front().unwrap();
 
-		let value = self.pop_while(|x| {
-			let Some(token) = x else {
-				return Err(format!("expected expression, got nothing"))?;
-			};
-
-			Ok(*token != Token::SemiColon)
-		})?;
+		let value = self.pop_until(Token::SemiColon)?;
 
 		let value = if value.is_empty() {
 			None
 		} else {
-			Some(Self::new(value).parse_expression()?)
+			Some(self.sub_parser(value, self.next()).parse_expression()?)
 		};
 
+		let end = self.expect(Token::SemiColon)?;
+
+		Ok(Some(ast::Return::new(start.to(&end), value)))
+	}
+
+	/// Creates a parser over `tokens`, which were just popped from `self`, so
+	/// that running out of them reports `next`, the token that followed them.
+	fn sub_parser(&self, tokens: Vec<(Token, Span)>, next: Option<(Token, Span)>) -> Self {
+		let end = tokens.last().map(|(_, span)| span.after()).or(self.end);
+
+		Self {
+			tokens: tokens.into(),
+			next,
+			end,
+			diagnostics: Vec::new(),
+		}
+	}
+
+	/// The token after everything popped so far, even past the end of a sub
+	/// parser's tokens.
+	fn next(&self) -> Option<(Token, Span)> {
+		self.tokens.front().or(self.next.as_ref()).cloned()
+	}
+
+	/// Pops a run of `///` lines, joining them into one comment.
+	fn pop_doc_comment(&mut self) -> Option<(String, Span)> {
+		let lines = self.pop_while(|x| matches!(x, Token::DocComment(_)));
+
+		let (_, start) = lines.first()?;
+		let (_, end) = lines.last()?;
+		let span = start.to(end);
+
+		let text = lines
+			.into_iter()
+			.filter_map(|(x, _)| match x {
+				Token::DocComment(x) => Some(x),
+				_ => None,
+			})
+			.collect::<Vec<_>>()
+			.join("\n");
+
+		Some((text, span))
+	}
+
+	/// Doc comments that document nothing are read as plain comments, with a
+	/// warning in case they were meant for something.
+	fn unused_doc_comment(span: &Span) -> Diagnostic {
+		Diagnostic::warning("unused doc comment")
+			.with_code(diagnostic::UNUSED_DOC_COMMENT)
+			.with_primary(span, "doc comments can only document a `fn`")
+			.with_note("use `//` for comments that don't document a function")
+	}
+
+	/// Reports `found` where `expected` should be, where `None` means the
+	/// tokens ran out and whatever follows them was found instead.
+	fn unexpected(&self, expected: &str, found: Option<(Token, Span)>) -> Diagnostic {
+		match found.or_else(|| self.next.clone()) {
+			Some((token, span)) => Diagnostic::error(format!("expected {expected}, found {token}"))
+				.with_code(diagnostic::UNEXPECTED_TOKEN)
+				.with_primary(&span, format!("expected {expected}")),
+			None => {
+				let ret = Diagnostic::error(format!("expected {expected}, found end of input"))
+					.with_code(diagnostic::UNEXPECTED_TOKEN);
+
+				match &self.end {
+					Some(span) => ret.with_primary(span, format!("expected {expected}")),
+					None => ret,
+				}
+			}
+		}
+	}
+
+	fn expect(&mut self, token: Token) -> Result<Span> {
 		match self.tokens.pop_front() {
-			Some((Token::SemiColon, _)) => {}
-			x => return Err(format!("expected SemiColon, got {x:?}")),
+			Some((x, span)) if x == token => Ok(span),
+			x => Err(self.unexpected(&token.to_string(), x)),
 		}
+	}
 
-		Ok(Some(ast::Return::new(location, value)))
+	fn expect_identifier(&mut self) -> Result<(String, Span)> {
+		match self.tokens.pop_front() {
+			Some((Token::Identifier(x), span)) => Ok((x, span)),
+			x => Err(self.unexpected("an identifier", x)),
+		}
 	}
 
-	fn pop_while(
-		&mut self,
-		mut predicate: impl FnMut(Option<&Token>) -> Result<bool>,
-	) -> Result<Vec<(Token, Location)>> {
+	fn pop_while(&mut self, mut predicate: impl FnMut(&Token) -> bool) -> Vec<(Token, Span)> {
 		let mut ret = vec![];
 
-		while predicate(self.tokens.front().map(|x| &x.0))? {
+		while let Some((token, _)) = self.tokens.front() {
+			if !predicate(token) {
+				break;
+			}
+
 			ret.push(self.tokens.pop_front().unwrap());
 		}
 
-		Ok(ret)
+		ret
 	}
 
-	fn pop_scope(&mut self, open: Token, close: Token) -> Result<Vec<(Token, Location)>> {
-		let first = self
-			.tokens
-			.pop_front()
-			.ok_or_else(|| format!("expected {open:?}, got nothing"))?;
+	/// Pops every token up to, but not including, `terminator`.
+	fn pop_until(&mut self, terminator: Token) -> Result<Vec<(Token, Span)>> {
+		let ret = self.pop_while(|x| *x != terminator);
 
-		if first.0 != open {
-			Err(format!("expected {open:?}, got {:?}", first.0))?;
+		if self.tokens.is_empty() {
+			let expected = terminator.to_string();
+			return Err(self
+				.sub_parser(ret, self.next())
+				.unexpected(&expected, None));
 		}
 
-		let mut diff = 1;
+		Ok(ret)
+	}
+
+	/// Pops a balanced `open ... close` group.
+	fn pop_scope(&mut self, open: Token, close: Token) -> Result<Group> {
+		self.pop_scope_before(open, close, &[])
+	}
+
+	/// Pops a balanced `open ... close` group, treating any of `stop` as a
+	/// missing `close` and leaving it in place.
+	fn pop_scope_before(&mut self, open: Token, close: Token, stop: &[Token]) -> Result<Group> {
+		let start = match self.tokens.pop_front() {
+			Some((x, span)) if x == open => span,
+			x => return Err(self.unexpected(&open.to_string(), x)),
+		};
+
+		let mut depth = 1;
 
 		let ret = self.pop_while(|x| {
-			let Some(token) = x else {
-				return Err(format!("expected {close:?}, got nothing"))?;
-			};
+			if stop.contains(x) {
+				return false;
+			} else if *x == open {
+				depth += 1;
+			} else if *x == close {
+				depth -= 1;
+			}
+
+			depth > 0
+		});
+
+		let (token, end) = match self.tokens.front() {
+			Some((x, _)) if *x == close => self.tokens.pop_front().unwrap(),
+			Some(x) => {
+				return Err(self
+					.unexpected(&close.to_string(), Some(x.clone()))
+					.with_secondary(&start, "unclosed delimiter"));
+			}
+			None => {
+				let ret = self.sub_parser(ret, self.next());
 
-			if *token == open {
-				diff += 1;
-			} else if *token == close {
-				diff -= 1;
+				return Err(ret
+					.unexpected(&close.to_string(), None)
+					.with_secondary(&start, "unclosed delimiter"));
 			}
+		};
+
+		Ok(Group {
+			tokens: ret,
+			span: start.to(&end),
+			close: (token, end),
+		})
+	}
+}
 
-			Ok(diff > 0)
-		})?;
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{Lexer, SourceMap};
 
-		let last = self
-			.tokens
-			.pop_front()
-			.ok_or_else(|| format!("expected {close:?}, got nothing"))?;
+	fn parse(source: &str) -> Vec<Diagnostic> {
+		let mut sources = SourceMap::new();
+		let file = sources.add("test.y", source);
+		let tokens = Lexer::new(&sources, file).lex().unwrap();
 
-		if last.0 != close {
-			Err(format!("expected {close:?}, got {:?}", last.0))?
-		}
+		Parser::new(tokens).parse_global_scope().1
+	}
 
-		Ok(ret)
+	#[test]
+	fn recovers_from_unclosed_parameter_lists() {
+		let diagnostics =
+			parse("fn g(a: i64 {\n\treturn a;\n}\nfn h(b: i64\nfn k() -> i64 { return 1 +; }\n");
+
+		let messages = diagnostics
+			.iter()
+			.map(|x| x.message.as_str())
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			messages,
+			[
+				"expected `)`, found `{`",
+				"expected `)`, found `fn`",
+				"expected an expression, found `;`",
+			]
+		);
 	}
 }
@@ -0,0 +1,129 @@
+use crate::{Diagnostic, Location, Result, Span};
+
+/// Identifies a file in a `SourceMap`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+struct SourceFile {
+	name: String,
+	contents: String,
+	/// Byte offset at which every line starts.
+	line_starts: Vec<u32>,
+}
+
+/// Owns the text of every file being compiled, so that spans only need to
+/// store a `FileId` and byte offsets.
+#[derive(Default)]
+pub struct SourceMap {
+	files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Reads the file at `path` and adds it under that name.
+	pub fn load(&mut self, path: &str) -> Result<FileId> {
+		let contents =
+			std::fs::read_to_string(path).map_err(|x| Diagnostic::error(format!("{path}: {x}")))?;
+
+		Ok(self.add(path, contents))
+	}
+
+	pub fn add(&mut self, name: impl Into<String>, contents: impl Into<String>) -> FileId {
+		let contents = contents.into();
+
+		let line_starts = std::iter::once(0)
+			.chain(contents.match_indices('\n').map(|(x, _)| x as u32 + 1))
+			.collect();
+
+		self.files.push(SourceFile {
+			name: name.into(),
+			contents,
+			line_starts,
+		});
+
+		FileId(self.files.len() as u32 - 1)
+	}
+
+	pub fn name(&self, file: FileId) -> &str {
+		&self.file(file).name
+	}
+
+	pub fn contents(&self, file: FileId) -> &str {
+		&self.file(file).contents
+	}
+
+	/// Returns the text of the 1-based `line` of `file`, without its newline.
+	pub fn line(&self, file: FileId, line: usize) -> Option<&str> {
+		let file = self.file(file);
+
+		let start = *file.line_starts.get(line.checked_sub(1)?)? as usize;
+		let end = file
+			.line_starts
+			.get(line)
+			.map_or(file.contents.len(), |x| *x as usize);
+
+		Some(file.contents[start..end].trim_end_matches(['\n', '\r']))
+	}
+
+	/// Resolves a byte offset into `file` to a line and column.
+	pub fn location(&self, file: FileId, offset: u32) -> Location {
+		let source = self.file(file);
+
+		let line = source.line_starts.partition_point(|x| *x <= offset);
+		let start = source.line_starts[line - 1] as usize;
+
+		Location {
+			file_name: source.name.clone(),
+			line,
+			column: source.contents[start..offset as usize].chars().count() + 1,
+		}
+	}
+
+	pub fn start(&self, span: &Span) -> Location {
+		self.location(span.file, span.start)
+	}
+
+	pub fn end(&self, span: &Span) -> Location {
+		self.location(span.file, span.end)
+	}
+
+	fn file(&self, file: FileId) -> &SourceFile {
+		&self.files[file.0 as usize]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn resolves_lines_and_columns() {
+		let mut sources = SourceMap::new();
+		let first = sources.add("first.y", "fn main() {\n\t/* é */ x\n}");
+		let second = sources.add("second.y", "\n\n");
+
+		let location = sources.location(first, 0);
+		assert_eq!((location.line, location.column), (1, 1));
+
+		// columns count characters, not bytes
+		let location = sources.location(first, 22);
+		assert_eq!((location.line, location.column), (2, 10));
+		assert_eq!(location.to_string(), "first.y:2:10");
+
+		let location = sources.location(first, 24);
+		assert_eq!((location.line, location.column), (3, 1));
+
+		assert_eq!(sources.line(first, 2), Some("\t/* é */ x"));
+		assert_eq!(sources.line(first, 4), None);
+
+		let location = sources.location(second, 2);
+		assert_eq!(
+			(location.file_name.as_str(), location.line),
+			("second.y", 3)
+		);
+		assert_eq!(sources.line(second, 3), Some(""));
+	}
+}
@@ -0,0 +1,26 @@
+use crate::FileId;
+
+/// A range of source text, from the byte offset `start` up to but not
+/// including `end`. Use a `SourceMap` to turn it into lines and columns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Span {
+	pub file: FileId,
+	pub start: u32,
+	pub end: u32,
+}
+
+impl Span {
+	pub fn new(file: FileId, start: u32, end: u32) -> Self {
+		Self { file, start, end }
+	}
+
+	/// Returns a span from the start of `self` to the end of `other`.
+	pub fn to(&self, other: &Span) -> Self {
+		Self::new(self.file, self.start, other.end)
+	}
+
+	/// Returns an empty span just past the end of `self`.
+	pub fn after(&self) -> Self {
+		Self::new(self.file, self.end, self.end)
+	}
+}
@@ -1,12 +1,28 @@
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
 	Identifier(String),
-	Number(String),
+	Integer {
+		value: u64,
+		/// The type written after the digits, as in `10u8`.
+		suffix: Option<String>,
+	},
+	Char(char),
+	String(String),
+	/// The text of a `///` comment, without the slashes.
+	DocComment(String),
 
 	If,
+	Else,
 	Fn,
 	Return,
 	Let,
+	Mut,
+	As,
+	While,
+	Break,
+	Continue,
+	True,
+	False,
 
 	OpeningParen,
 	ClosingParen,
@@ -21,9 +37,25 @@ pub enum Token {
 	Minus,
 	Star,
 	Slash,
+	Percent,
 	Equals,
 	ExclamationMark,
 	QuestionMark,
+	LessThan,
+	GreaterThan,
+
+	EqualsEquals,
+	ExclamationMarkEquals,
+	LessThanEquals,
+	GreaterThanEquals,
+	Arrow,
+	PlusEquals,
+	MinusEquals,
+	StarEquals,
+	SlashEquals,
+	PercentEquals,
+	AmpersandAmpersand,
+	PipePipe,
 }
 
 impl Token {
@@ -38,3 +70,61 @@ impl Token {
 		}
 	}
 }
+
+impl std::fmt::Display for Token {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let text = match self {
+			Token::Identifier(x) => return write!(f, "identifier `{x}`"),
+			Token::Integer { value, suffix } => {
+				let suffix = suffix.as_deref().unwrap_or_default();
+				return write!(f, "integer `{value}{suffix}`");
+			}
+			Token::Char(x) => return write!(f, "character {x:?}"),
+			Token::String(x) => return write!(f, "string {x:?}"),
+			Token::DocComment(_) => return write!(f, "doc comment"),
+			Token::If => "if",
+			Token::Else => "else",
+			Token::Fn => "fn",
+			Token::Return => "return",
+			Token::Let => "let",
+			Token::Mut => "mut",
+			Token::As => "as",
+			Token::While => "while",
+			Token::Break => "break",
+			Token::Continue => "continue",
+			Token::True => "true",
+			Token::False => "false",
+			Token::OpeningParen => "(",
+			Token::ClosingParen => ")",
+			Token::OpeningCurly => "{",
+			Token::ClosingCurly => "}",
+			Token::Colon => ":",
+			Token::SemiColon => ";",
+			Token::Comma => ",",
+			Token::Plus => "+",
+			Token::Minus => "-",
+			Token::Star => "*",
+			Token::Slash => "/",
+			Token::Percent => "%",
+			Token::Equals => "=",
+			Token::ExclamationMark => "!",
+			Token::QuestionMark => "?",
+			Token::LessThan => "<",
+			Token::GreaterThan => ">",
+			Token::EqualsEquals => "==",
+			Token::ExclamationMarkEquals => "!=",
+			Token::LessThanEquals => "<=",
+			Token::GreaterThanEquals => ">=",
+			Token::Arrow => "->",
+			Token::PlusEquals => "+=",
+			Token::MinusEquals => "-=",
+			Token::StarEquals => "*=",
+			Token::SlashEquals => "/=",
+			Token::PercentEquals => "%=",
+			Token::AmpersandAmpersand => "&&",
+			Token::PipePipe => "||",
+		};
+
+		write!(f, "`{text}`")
+	}
+}
@@ -0,0 +1,643 @@
+use crate::x86_64::Register;
+
+/// A memory operand, `[base + displacement]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Memory {
+	pub base: Register,
+	pub displacement: i32,
+}
+
+impl Memory {
+	pub fn new(base: Register, displacement: i32) -> Self {
+		Self { base, displacement }
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+	Register(Register),
+	Memory(Memory),
+	Immediate(i64),
+}
+
+impl From<Register> for Operand {
+	fn from(value: Register) -> Self {
+		Operand::Register(value)
+	}
+}
+
+impl From<Memory> for Operand {
+	fn from(value: Memory) -> Self {
+		Operand::Memory(value)
+	}
+}
+
+impl From<i64> for Operand {
+	fn from(value: i64) -> Self {
+		Operand::Immediate(value)
+	}
+}
+
+impl From<i32> for Operand {
+	fn from(value: i32) -> Self {
+		Operand::Immediate(value.into())
+	}
+}
+
+impl From<u8> for Operand {
+	fn from(value: u8) -> Self {
+		Operand::Immediate(value.into())
+	}
+}
+
+/// A condition code, as encoded in the low nibble of `jcc` and `setcc`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+	Below = 0x2,
+	AboveEqual = 0x3,
+	Equal = 0x4,
+	NotEqual = 0x5,
+	BelowEqual = 0x6,
+	Above = 0x7,
+	Sign = 0x8,
+	NotSign = 0x9,
+	Less = 0xC,
+	GreaterEqual = 0xD,
+	LessEqual = 0xE,
+	Greater = 0xF,
+}
+
+impl Condition {
+	/// Returns the condition that holds exactly when `self` doesn't.
+	pub fn negate(self) -> Self {
+		match self {
+			Condition::Below => Condition::AboveEqual,
+			Condition::AboveEqual => Condition::Below,
+			Condition::Equal => Condition::NotEqual,
+			Condition::NotEqual => Condition::Equal,
+			Condition::BelowEqual => Condition::Above,
+			Condition::Above => Condition::BelowEqual,
+			Condition::Sign => Condition::NotSign,
+			Condition::NotSign => Condition::Sign,
+			Condition::Less => Condition::GreaterEqual,
+			Condition::GreaterEqual => Condition::Less,
+			Condition::LessEqual => Condition::Greater,
+			Condition::Greater => Condition::LessEqual,
+		}
+	}
+}
+
+/// A position in the code that jumps and calls can refer to before it's
+/// known.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Label(usize);
+
+/// Encodes instructions into machine code. Operands are 64 bits wide unless
+/// the method says otherwise.
+#[derive(Default)]
+pub struct Assembler {
+	code: Vec<u8>,
+	/// Where every label was bound, if it has been yet.
+	labels: Vec<Option<usize>>,
+	/// `(position, label)` pairs for every `rel32` that points at a label.
+	fixups: Vec<(usize, Label)>,
+}
+
+impl Assembler {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// The offset of the next instruction.
+	pub fn position(&self) -> usize {
+		self.code.len()
+	}
+
+	pub fn label(&mut self) -> Label {
+		self.labels.push(None);
+		Label(self.labels.len() - 1)
+	}
+
+	/// Points `label` at the next instruction.
+	pub fn bind(&mut self, label: Label) {
+		self.bind_at(label, self.position());
+	}
+
+	/// Points `label` at `position`, which may be past the end of the code
+	/// for code that gets placed right after it.
+	pub fn bind_at(&mut self, label: Label, position: usize) {
+		assert!(self.labels[label.0].is_none(), "label bound twice");
+		self.labels[label.0] = Some(position);
+	}
+
+	/// Returns the code with every jump and call resolved.
+	pub fn finish(mut self) -> Vec<u8> {
+		for (position, label) in std::mem::take(&mut self.fixups) {
+			let target = self.labels[label.0].expect("label was never bound");
+			let relative = target.wrapping_sub(position + 4) as u32;
+
+			self.code[position..position + 4].copy_from_slice(&relative.to_le_bytes());
+		}
+
+		self.code
+	}
+
+	pub fn mov(&mut self, dst: impl Into<Operand>, src: impl Into<Operand>) {
+		match (dst.into(), src.into()) {
+			(dst @ Operand::Register(_), Operand::Register(src))
+			| (dst @ Operand::Memory(_), Operand::Register(src)) => {
+				self.emit_modrm(true, false, &[0x89], src.index(), dst);
+			}
+			(Operand::Register(dst), src @ Operand::Memory(_)) => {
+				self.emit_modrm(true, false, &[0x8B], dst.index(), src);
+			}
+			(Operand::Register(dst), Operand::Immediate(imm)) => {
+				if let Ok(imm) = u32::try_from(imm) {
+					// writing the 32-bit register clears the upper half
+					self.emit_rex(false, false, 0, dst.index());
+					self.emit([0xB8 | (dst.index() & 7)]);
+					self.emit(imm.to_le_bytes());
+				} else if let Ok(imm) = i32::try_from(imm) {
+					self.emit_modrm(true, false, &[0xC7], 0, Operand::Register(dst));
+					self.emit(imm.to_le_bytes());
+				} else {
+					self.emit_rex(true, false, 0, dst.index());
+					self.emit([0xB8 | (dst.index() & 7)]);
+					self.emit(imm.to_le_bytes());
+				}
+			}
+			(dst @ Operand::Memory(_), Operand::Immediate(imm)) => {
+				let imm = i32::try_from(imm).expect("immediate doesn't fit in 32 bits");
+
+				self.emit_modrm(true, false, &[0xC7], 0, dst);
+				self.emit(imm.to_le_bytes());
+			}
+			(dst, src) => panic!("can't encode `mov {dst:?}, {src:?}`"),
+		}
+	}
+
+	/// Stores the low byte of `src` at `dst`.
+	pub fn mov_byte(&mut self, dst: Memory, src: impl Into<Operand>) {
+		match src.into() {
+			Operand::Register(src) => {
+				self.emit_modrm(false, true, &[0x88], src.index(), Operand::Memory(dst));
+			}
+			Operand::Immediate(imm) => {
+				self.emit_modrm(false, false, &[0xC6], 0, Operand::Memory(dst));
+				self.emit([imm as u8]);
+			}
+			src => panic!("can't encode `mov byte {dst:?}, {src:?}`"),
+		}
+	}
+
+	pub fn lea(&mut self, dst: Register, src: Memory) {
+		self.emit_modrm(true, false, &[0x8D], dst.index(), Operand::Memory(src));
+	}
+
+	/// Emits `lea dst, [rip + rel32]` with a zero displacement, returning the
+	/// position of the displacement for the caller to fill in.
+	pub fn lea_rip(&mut self, dst: Register) -> usize {
+		self.emit_rex(true, false, dst.index(), 0);
+		self.emit([0x8D, 0x05 | (dst.index() & 7) << 3]);
+
+		let position = self.position();
+		self.emit([0x00, 0x00, 0x00, 0x00]);
+
+		position
+	}
+
+	pub fn add(&mut self, dst: impl Into<Operand>, src: impl Into<Operand>) {
+		self.emit_arithmetic(0, dst.into(), src.into());
+	}
+
+	pub fn sub(&mut self, dst: impl Into<Operand>, src: impl Into<Operand>) {
+		self.emit_arithmetic(5, dst.into(), src.into());
+	}
+
+	pub fn xor(&mut self, dst: impl Into<Operand>, src: impl Into<Operand>) {
+		self.emit_arithmetic(6, dst.into(), src.into());
+	}
+
+	pub fn cmp(&mut self, lhs: impl Into<Operand>, rhs: impl Into<Operand>) {
+		self.emit_arithmetic(7, lhs.into(), rhs.into());
+	}
+
+	pub fn test(&mut self, lhs: Register, rhs: Register) {
+		self.emit_modrm(true, false, &[0x85], rhs.index(), Operand::Register(lhs));
+	}
+
+	/// Signed `dst *= src`, keeping the low 64 bits.
+	pub fn imul(&mut self, dst: Register, src: Register) {
+		self.emit_modrm(
+			true,
+			false,
+			&[0x0F, 0xAF],
+			dst.index(),
+			Operand::Register(src),
+		);
+	}
+
+	/// Unsigned `rdx:rax = rax * src`.
+	pub fn mul(&mut self, src: Register) {
+		self.emit_modrm(true, false, &[0xF7], 4, Operand::Register(src));
+	}
+
+	/// Unsigned division of `rdx:rax` by `src`, leaving the quotient in `rax`
+	/// and the remainder in `rdx`.
+	pub fn div(&mut self, src: Register) {
+		self.emit_modrm(true, false, &[0xF7], 6, Operand::Register(src));
+	}
+
+	/// Signed counterpart of `div`.
+	pub fn idiv(&mut self, src: Register) {
+		self.emit_modrm(true, false, &[0xF7], 7, Operand::Register(src));
+	}
+
+	/// Sign-extends `rax` into `rdx`.
+	pub fn cqo(&mut self) {
+		self.emit([0x48, 0x99]);
+	}
+
+	pub fn neg(&mut self, dst: Register) {
+		self.emit_modrm(true, false, &[0xF7], 3, Operand::Register(dst));
+	}
+
+	pub fn dec(&mut self, dst: Register) {
+		self.emit_modrm(true, false, &[0xFF], 1, Operand::Register(dst));
+	}
+
+	/// Sign-extends the low `size` bytes of `src` into `dst`.
+	pub fn movsx(&mut self, dst: Register, src: Register, size: usize) {
+		let opcode: &[u8] = match size {
+			1 => &[0x0F, 0xBE],
+			2 => &[0x0F, 0xBF],
+			4 => &[0x63],
+			_ => panic!("can't sign-extend {size} bytes"),
+		};
+
+		self.emit_modrm(true, size == 1, opcode, dst.index(), Operand::Register(src));
+	}
+
+	/// Zero-extends the low `size` bytes of `src` into `dst`.
+	pub fn movzx(&mut self, dst: Register, src: Register, size: usize) {
+		let src = Operand::Register(src);
+
+		// these write the 32-bit register, which clears the upper half
+		match size {
+			1 => self.emit_modrm(false, true, &[0x0F, 0xB6], dst.index(), src),
+			2 => self.emit_modrm(false, false, &[0x0F, 0xB7], dst.index(), src),
+			4 => self.emit_modrm(false, false, &[0x8B], dst.index(), src),
+			_ => panic!("can't zero-extend {size} bytes"),
+		}
+	}
+
+	/// Sets the low byte of `dst` to whether `condition` holds.
+	pub fn setcc(&mut self, condition: Condition, dst: Register) {
+		self.emit_modrm(
+			false,
+			true,
+			&[0x0F, 0x90 | condition as u8],
+			0,
+			Operand::Register(dst),
+		);
+	}
+
+	pub fn push(&mut self, src: Register) {
+		self.emit_rex(false, false, 0, src.index());
+		self.emit([0x50 | (src.index() & 7)]);
+	}
+
+	pub fn pop(&mut self, dst: Register) {
+		self.emit_rex(false, false, 0, dst.index());
+		self.emit([0x58 | (dst.index() & 7)]);
+	}
+
+	pub fn jmp(&mut self, target: Label) {
+		self.emit_jump(&[0xEB], &[0xE9], target);
+	}
+
+	pub fn jcc(&mut self, condition: Condition, target: Label) {
+		let condition = condition as u8;
+		self.emit_jump(&[0x70 | condition], &[0x0F, 0x80 | condition], target);
+	}
+
+	/// Emits `call rel32`, returning the position of the `rel32`, which
+	/// object files need to relocate.
+	pub fn call(&mut self, target: Label) -> usize {
+		self.emit([0xE8]);
+		self.emit_fixup(target)
+	}
+
+	pub fn ret(&mut self) {
+		self.emit([0xC3]);
+	}
+
+	pub fn syscall(&mut self) {
+		self.emit([0x0F, 0x05]);
+	}
+
+	/// Emits a jump, using the `rel8` form when the target is already known
+	/// and close enough.
+	fn emit_jump(&mut self, short: &[u8], near: &[u8], target: Label) {
+		if let Some(target) = self.labels[target.0] {
+			let end = self.position() + short.len() + 1;
+
+			if let Ok(relative) = i8::try_from(target as isize - end as isize) {
+				self.code.extend(short);
+				self.emit([relative as u8]);
+				return;
+			}
+		}
+
+		self.code.extend(near);
+		self.emit_fixup(target);
+	}
+
+	fn emit_fixup(&mut self, target: Label) -> usize {
+		let position = self.position();
+
+		self.fixups.push((position, target));
+		self.emit([0x00, 0x00, 0x00, 0x00]);
+
+		position
+	}
+
+	/// Emits one of the classic two-operand arithmetic instructions, where
+	/// `extension` picks the operation.
+	fn emit_arithmetic(&mut self, extension: u8, dst: Operand, src: Operand) {
+		match (dst, src) {
+			(dst @ Operand::Register(_), Operand::Register(src))
+			| (dst @ Operand::Memory(_), Operand::Register(src)) => {
+				self.emit_modrm(true, false, &[extension << 3 | 0x01], src.index(), dst);
+			}
+			(Operand::Register(dst), src @ Operand::Memory(_)) => {
+				self.emit_modrm(true, false, &[extension << 3 | 0x03], dst.index(), src);
+			}
+			(dst, Operand::Immediate(imm)) if !matches!(dst, Operand::Immediate(_)) => {
+				if let Ok(imm) = i8::try_from(imm) {
+					self.emit_modrm(true, false, &[0x83], extension, dst);
+					self.emit([imm as u8]);
+				} else {
+					let imm = i32::try_from(imm).expect("immediate doesn't fit in 32 bits");
+
+					self.emit_modrm(true, false, &[0x81], extension, dst);
+					self.emit(imm.to_le_bytes());
+				}
+			}
+			(dst, src) => panic!("can't encode arithmetic on {dst:?}, {src:?}"),
+		}
+	}
+
+	/// Emits the REX prefix, if any, for the ModRM `reg` field and the
+	/// register in the `rm` field or the SIB base. `byte` forces an empty one,
+	/// which makes 4 to 7 mean `spl` to `dil` instead of `ah` to `bh`.
+	fn emit_rex(&mut self, wide: bool, byte: bool, reg: u8, rm: u8) {
+		let rex = (wide as u8) << 3 | (reg >> 3) << 2 | rm >> 3;
+
+		if rex != 0 || byte {
+			self.emit([0x40 | rex]);
+		}
+	}
+
+	/// Emits `opcode` with its REX prefix and the ModRM, SIB and
+	/// displacement for `reg` and `rm`, using the shortest displacement that
+	/// fits. `byte` means the register operand, `rm` if it's a register and
+	/// `reg` otherwise, is 8 bits wide.
+	fn emit_modrm(&mut self, wide: bool, byte: bool, opcode: &[u8], reg: u8, rm: Operand) {
+		match rm {
+			Operand::Register(rm) => {
+				let byte = byte && (4..8).contains(&rm.index());
+				self.emit_rex(wide, byte, reg, rm.index());
+
+				self.code.extend(opcode);
+				self.emit([0xC0 | (reg & 7) << 3 | rm.index() & 7]);
+			}
+			Operand::Memory(Memory { base, displacement }) => {
+				let byte = byte && (4..8).contains(&reg);
+				self.emit_rex(wide, byte, reg, base.index());
+				self.code.extend(opcode);
+
+				let base = base.index() & 7;
+
+				// `[rbp]` and `[r13]` have no mode without a displacement
+				let mode = if displacement == 0 && base != 5 {
+					0x00
+				} else if i8::try_from(displacement).is_ok() {
+					0x40
+				} else {
+					0x80
+				};
+
+				self.emit([mode | (reg & 7) << 3 | base]);
+
+				// `rsp` and `r12` as the base need a SIB byte
+				if base == 4 {
+					self.emit([0x24]);
+				}
+
+				match mode {
+					0x40 => self.emit([displacement as u8]),
+					0x80 => self.emit(displacement.to_le_bytes()),
+					_ => {}
+				}
+			}
+			Operand::Immediate(_) => panic!("an immediate can't be a ModRM operand"),
+		}
+	}
+
+	fn emit<const N: usize>(&mut self, bytes: [u8; N]) {
+		self.code.extend(bytes);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use Register::*;
+
+	/// Emits something and the bytes it should come out as.
+	type Case = (fn(&mut Assembler), &'static [u8]);
+
+	fn assemble(f: impl FnOnce(&mut Assembler)) -> Vec<u8> {
+		let mut assembler = Assembler::new();
+		f(&mut assembler);
+		assembler.finish()
+	}
+
+	#[test]
+	fn encodes_known_instructions() {
+		let cases: &[Case] = &[
+			(|x| x.mov(Rax, Rcx), &[0x48, 0x89, 0xC8]),
+			(|x| x.mov(R12, Rsp), &[0x49, 0x89, 0xE4]),
+			(|x| x.mov(Rax, 1), &[0xB8, 0x01, 0x00, 0x00, 0x00]),
+			(
+				|x| x.mov(R10, 0xFFFF_FFFF_i64),
+				&[0x41, 0xBA, 0xFF, 0xFF, 0xFF, 0xFF],
+			),
+			(
+				|x| x.mov(Rax, -2),
+				&[0x48, 0xC7, 0xC0, 0xFE, 0xFF, 0xFF, 0xFF],
+			),
+			(
+				|x| x.mov(Rax, 0x1_2345_6789_i64),
+				&[0x48, 0xB8, 0x89, 0x67, 0x45, 0x23, 0x01, 0x00, 0x00, 0x00],
+			),
+			(
+				|x| x.mov(Memory::new(Rbp, -16), -1),
+				&[0x48, 0xC7, 0x45, 0xF0, 0xFF, 0xFF, 0xFF, 0xFF],
+			),
+			(
+				|x| x.lea(Rsi, Memory::new(Rsp, 32)),
+				&[0x48, 0x8D, 0x74, 0x24, 0x20],
+			),
+			(|x| x.add(Rsp, 8), &[0x48, 0x83, 0xC4, 0x08]),
+			(
+				|x| x.sub(Rsp, 0x1000),
+				&[0x48, 0x81, 0xEC, 0x00, 0x10, 0x00, 0x00],
+			),
+			(|x| x.cmp(Rax, Rcx), &[0x48, 0x39, 0xC8]),
+			(|x| x.xor(Rdx, Rdx), &[0x48, 0x31, 0xD2]),
+			(
+				|x| x.sub(Rax, Memory::new(Rbp, -16)),
+				&[0x48, 0x2B, 0x45, 0xF0],
+			),
+			(
+				|x| x.add(R11, Memory::new(Rsp, 8)),
+				&[0x4C, 0x03, 0x5C, 0x24, 0x08],
+			),
+			(|x| x.test(R8, R8), &[0x4D, 0x85, 0xC0]),
+			(|x| x.imul(Rax, R15), &[0x49, 0x0F, 0xAF, 0xC7]),
+			(|x| x.mul(Rcx), &[0x48, 0xF7, 0xE1]),
+			(|x| x.idiv(Rcx), &[0x48, 0xF7, 0xF9]),
+			(|x| x.cqo(), &[0x48, 0x99]),
+			(|x| x.neg(R10), &[0x49, 0xF7, 0xDA]),
+			(|x| x.dec(Rsi), &[0x48, 0xFF, 0xCE]),
+			(|x| x.movsx(Rax, Rax, 1), &[0x48, 0x0F, 0xBE, 0xC0]),
+			(|x| x.movsx(Rax, Rsi, 1), &[0x48, 0x0F, 0xBE, 0xC6]),
+			(|x| x.movsx(Rax, R9, 2), &[0x49, 0x0F, 0xBF, 0xC1]),
+			(|x| x.movsx(Rax, Rax, 4), &[0x48, 0x63, 0xC0]),
+			(|x| x.movzx(Rax, Rdi, 1), &[0x40, 0x0F, 0xB6, 0xC7]),
+			(|x| x.movzx(Rax, Rax, 2), &[0x0F, 0xB7, 0xC0]),
+			(|x| x.movzx(Rax, Rcx, 4), &[0x8B, 0xC1]),
+			(|x| x.setcc(Condition::Equal, Rax), &[0x0F, 0x94, 0xC0]),
+			(
+				|x| x.setcc(Condition::Below, Rsi),
+				&[0x40, 0x0F, 0x92, 0xC6],
+			),
+			(
+				|x| x.setcc(Condition::Greater, R12),
+				&[0x41, 0x0F, 0x9F, 0xC4],
+			),
+			(
+				|x| x.mov_byte(Memory::new(Rsi, 0), b'\n'),
+				&[0xC6, 0x06, 0x0A],
+			),
+			(|x| x.mov_byte(Memory::new(Rsi, 0), Rdx), &[0x88, 0x16]),
+			(
+				|x| x.mov_byte(Memory::new(Rsp, 0), Rdi),
+				&[0x40, 0x88, 0x3C, 0x24],
+			),
+			(
+				|x| x.mov_byte(Memory::new(R12, 0), R9),
+				&[0x45, 0x88, 0x0C, 0x24],
+			),
+			(|x| x.push(Rbp), &[0x55]),
+			(|x| x.push(R12), &[0x41, 0x54]),
+			(|x| x.pop(R15), &[0x41, 0x5F]),
+			(|x| x.ret(), &[0xC3]),
+			(|x| x.syscall(), &[0x0F, 0x05]),
+		];
+
+		for (f, bytes) in cases {
+			assert_eq!(assemble(f), *bytes);
+		}
+	}
+
+	#[test]
+	fn picks_displacement_sizes() {
+		let cases: &[(Memory, &[u8])] = &[
+			(Memory::new(Rax, 0), &[0x48, 0x8B, 0x00]),
+			(Memory::new(Rsp, 0), &[0x48, 0x8B, 0x04, 0x24]),
+			(Memory::new(Rsp, 8), &[0x48, 0x8B, 0x44, 0x24, 0x08]),
+			(Memory::new(Rsp, 127), &[0x48, 0x8B, 0x44, 0x24, 0x7F]),
+			(
+				Memory::new(Rsp, 128),
+				&[0x48, 0x8B, 0x84, 0x24, 0x80, 0x00, 0x00, 0x00],
+			),
+			// `[rbp]` and `[r13]` always need a displacement
+			(Memory::new(Rbp, 0), &[0x48, 0x8B, 0x45, 0x00]),
+			(Memory::new(R13, -8), &[0x49, 0x8B, 0x45, 0xF8]),
+			(Memory::new(Rbp, -128), &[0x48, 0x8B, 0x45, 0x80]),
+			(
+				Memory::new(Rbp, -129),
+				&[0x48, 0x8B, 0x85, 0x7F, 0xFF, 0xFF, 0xFF],
+			),
+			(
+				Memory::new(R12, 0x100),
+				&[0x49, 0x8B, 0x84, 0x24, 0x00, 0x01, 0x00, 0x00],
+			),
+		];
+
+		for (memory, bytes) in cases {
+			assert_eq!(assemble(|x| x.mov(Rax, *memory)), *bytes, "{memory:?}");
+		}
+	}
+
+	#[test]
+	fn resolves_labels() {
+		let mut assembler = Assembler::new();
+		let function = assembler.label();
+		let forward = assembler.label();
+		let backward = assembler.label();
+
+		assembler.bind(function);
+		assembler.bind(backward);
+		assembler.jcc(Condition::Equal, forward);
+		assembler.ret();
+		assembler.bind(forward);
+		assembler.jmp(backward);
+		assembler.jcc(Condition::NotSign, backward);
+		let call = assembler.call(function);
+
+		let expected: [&[u8]; 5] = [
+			// je forward
+			&[0x0F, 0x84, 0x01, 0x00, 0x00, 0x00],
+			// ret
+			&[0xC3],
+			// jmp backward
+			&[0xEB, 0xF7],
+			// jns backward
+			&[0x79, 0xF5],
+			// call function
+			&[0xE8, 0xF0, 0xFF, 0xFF, 0xFF],
+		];
+
+		assert_eq!(call, 12);
+		assert_eq!(assembler.finish(), expected.concat());
+	}
+
+	#[test]
+	fn binds_labels_past_the_end() {
+		let mut assembler = Assembler::new();
+		let after = assembler.label();
+
+		assembler.call(after);
+		assembler.bind_at(after, assembler.position() + 0x10);
+
+		assert_eq!(assembler.finish(), [0xE8, 0x10, 0x00, 0x00, 0x00]);
+	}
+
+	#[test]
+	fn uses_rel32_for_far_backward_jumps() {
+		let mut assembler = Assembler::new();
+		let start = assembler.label();
+
+		assembler.bind(start);
+		for _ in 0..0x80 {
+			assembler.ret();
+		}
+		assembler.jmp(start);
+
+		assert_eq!(assembler.finish()[0x80..], [0xE9, 0x7B, 0xFF, 0xFF, 0xFF]);
+	}
+}
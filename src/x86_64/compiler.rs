@@ -1,234 +1,592 @@
-use crate::{llir, x86_64::Executable};
+use crate::{
+	llir,
+	x86_64::{
+		Allocation, Assembler, Condition, Frame, Label, Location, Memory, Register, SYS_EXIT,
+		SYS_WRITE,
+	},
+	Result,
+};
+
+/// The System V integer argument registers, in order.
+const ARGUMENT_REGISTERS: [Register; 6] = [
+	Register::Rdi,
+	Register::Rsi,
+	Register::Rdx,
+	Register::Rcx,
+	Register::R8,
+	Register::R9,
+];
+
+pub struct Symbol {
+	pub name: String,
+	pub offset: usize,
+	pub size: usize,
+}
+
+pub struct Program {
+	pub code: Vec<u8>,
+	/// Read-only data that `code` refers to, like string literals.
+	pub rodata: Vec<u8>,
+	/// Where `main` starts, if the program has one.
+	pub entry_point_offset: Option<usize>,
+	pub symbols: Vec<Symbol>,
+	/// `(position, function)` pairs for every `call rel32`, where `function`
+	/// indexes into `symbols`
+	pub function_fixups: Vec<(usize, usize)>,
+	/// `(position, offset)` pairs for every RIP-relative `rel32` that points
+	/// at `offset` in `rodata`. These are left as zero in `code`, since only
+	/// the output knows where `rodata` ends up.
+	pub data_fixups: Vec<(usize, usize)>,
+}
+
+impl Program {
+	/// Returns `code` with every `data_fixups` entry resolved, for when
+	/// `rodata` is placed `rodata_offset` bytes after the start of `code`.
+	pub fn relocate(&self, rodata_offset: usize) -> Vec<u8> {
+		let mut ret = self.code.clone();
+
+		for (position, offset) in &self.data_fixups {
+			let relative = (rodata_offset + offset).wrapping_sub(position + 4) as u32;
+			ret[*position..*position + 4].copy_from_slice(&relative.to_le_bytes());
+		}
+
+		ret
+	}
+}
 
 pub struct Compiler {
-	output: Vec<u8>,
-	positions: Vec<usize>,
-	function_positions: Vec<usize>,
-	branch_fixups: Vec<(usize, usize)>,
+	assembler: Assembler,
+	/// One label per function, so calls can refer to functions that come
+	/// later.
+	functions: Vec<Label>,
+	/// One label per node of the function being compiled, for branches.
+	nodes: Vec<Label>,
 	function_fixups: Vec<(usize, usize)>,
-	entry_point_offset: usize,
+	entry_point_offset: Option<usize>,
+	symbols: Vec<Symbol>,
+	rodata: Vec<u8>,
+	/// Offsets of the strings already in `rodata`.
+	strings: std::collections::HashMap<String, usize>,
+	data_fixups: Vec<(usize, usize)>,
+	/// Where the registers of the function being compiled live.
+	allocation: Allocation,
+	frame: Frame,
+	spill_all: bool,
 }
 
 impl Compiler {
 	pub fn new() -> Self {
 		Self {
-			output: Vec::new(),
-			positions: Vec::new(),
-			function_positions: Vec::new(),
-			branch_fixups: Vec::new(),
+			assembler: Assembler::new(),
+			functions: Vec::new(),
+			nodes: Vec::new(),
 			function_fixups: Vec::new(),
-			entry_point_offset: 0,
+			entry_point_offset: None,
+			symbols: Vec::new(),
+			rodata: Vec::new(),
+			strings: std::collections::HashMap::new(),
+			data_fixups: Vec::new(),
+			allocation: Allocation::default(),
+			frame: Frame::default(),
+			spill_all: false,
 		}
 	}
 
-	pub fn compile(mut self, functions: Vec<llir::Function>) -> Executable {
-		for i in functions {
+	/// Keeps every register on the stack instead of allocating machine
+	/// registers, for debugging.
+	pub fn with_spill_all(mut self, spill_all: bool) -> Self {
+		self.spill_all = spill_all;
+		self
+	}
+
+	pub fn compile(mut self, functions: Vec<llir::Function>) -> Result<Program> {
+		self.functions = functions.iter().map(|_| self.assembler.label()).collect();
+
+		for (label, i) in self.functions.clone().into_iter().zip(functions) {
+			let start = self.assembler.position();
+			self.assembler.bind(label);
+
 			if i.is_entry_point {
-				self.entry_point_offset = self.output.len();
+				self.entry_point_offset = Some(start);
 			}
 
-			self.function_positions.push(self.output.len());
-
-			self.emit([0x55]);
+			self.allocation = if self.spill_all {
+				Allocation::spill_all(&i)
+			} else {
+				Allocation::linear_scan(&i)
+			};
 
-			self.emit([0x48, 0x89, 0xE5]);
+			self.frame = Frame::new(&i.name, self.allocation.slot_count)?;
 
-			self.emit([0x48, 0x81, 0xEC]);
-			self.emit(((i.register_count * 8) as u32).to_ne_bytes());
+			self.assembler.push(Register::Rbp);
+			self.assembler.mov(Register::Rbp, Register::Rsp);
 
-			for (index, _size) in i.parameters.into_iter().enumerate() {
-				match index {
-					0 => {
-						// mov [rsp + (index * 8)], rdi
-						self.emit([0x48, 0x89, 0xBC, 0x24]);
-						self.emit(((index * 8) as u32).to_ne_bytes());
-					}
-					_ => todo!(),
-				}
+			if self.frame.size() != 0 {
+				self.assembler.sub(Register::Rsp, self.frame.size());
 			}
 
-			for i in i.body {
-				self.positions.push(self.output.len());
-				self.compile_node(i);
+			for (register, slot) in self.allocation.saved.clone() {
+				self.store_slot(slot, register);
 			}
 
-			for (position, target) in self.branch_fixups.clone() {
-				let target = self.positions.get(target).unwrap();
-				self.encode_relative_32(position, *target);
+			for (index, _size) in i.parameters.into_iter().enumerate() {
+				if let Some(argument) = ARGUMENT_REGISTERS.get(index) {
+					self.store(llir::Register(index), *argument);
+				} else {
+					let argument = self.frame.argument(index - ARGUMENT_REGISTERS.len());
+
+					self.assembler.mov(Register::Rax, argument);
+					self.store(llir::Register(index), Register::Rax);
+				}
 			}
 
-			self.branch_fixups.clear();
-			self.positions.clear();
-		}
+			self.nodes = i.body.iter().map(|_| self.assembler.label()).collect();
 
-		for (position, target) in self.function_fixups.clone() {
-			let target = self.function_positions.get(target).unwrap();
-			self.encode_relative_32(position, *target);
-		}
+			for (label, i) in self.nodes.clone().into_iter().zip(i.body) {
+				self.assembler.bind(label);
+				self.compile_node(i);
+			}
 
-		for i in &self.output {
-			println!("{i:02X}");
+			self.symbols.push(Symbol {
+				name: i.name,
+				offset: start,
+				size: self.assembler.position() - start,
+			});
 		}
 
-		Executable::new(self.output, self.entry_point_offset)
+		Ok(Program {
+			code: self.assembler.finish(),
+			rodata: self.rodata,
+			entry_point_offset: self.entry_point_offset,
+			symbols: self.symbols,
+			function_fixups: self.function_fixups,
+			data_fixups: self.data_fixups,
+		})
 	}
 
 	fn compile_node(&mut self, node: llir::Node) {
 		match node {
-			llir::Node::Move { dst, src } => {
-				// mov rax, [rsp + src * 8]
-				self.emit([0x48, 0x8B, 0x84, 0x24]);
-				self.emit(((src.0 * 8) as u32).to_ne_bytes());
-
-				// mov [rsp + dst * 8], rax
-				self.emit([0x48, 0x89, 0x84, 0x24]);
-				self.emit(((dst.0 * 8) as u32).to_ne_bytes());
-			}
+			llir::Node::Move { dst, src } => match self.allocation.location(dst) {
+				Location::Register(dst) => self.load(dst, src),
+				Location::Stack(_) => {
+					self.load(Register::Rax, src);
+					self.store(dst, Register::Rax);
+				}
+			},
 			llir::Node::MoveImmI64 { dst, imm } => {
-				// mov rax, imm
-				self.emit([0x48, 0xB8]);
-				self.emit(imm.to_ne_bytes());
-
-				// mov [rsp + dst * 8], rax
-				self.emit([0x48, 0x89, 0x84, 0x24]);
-				self.emit(((dst.0 * 8) as u32).to_ne_bytes());
+				self.assembler.mov(Register::Rax, imm);
+				self.store(dst, Register::Rax);
 			}
-			llir::Node::Jump { target } => {
-				// jmp target
-				self.emit([0xE9]);
+			llir::Node::MoveString { dst, value } => {
+				let offset = self.intern(value);
 
-				let pos = self.output.len();
-				self.emit([0x00, 0x00, 0x00, 0x00]);
+				let position = self.assembler.lea_rip(Register::Rax);
+				self.data_fixups.push((position, offset));
 
-				self.branch_fixups.push((pos, target));
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Jump { target } => {
+				self.assembler.jmp(self.nodes[target]);
 			}
 			llir::Node::JumpOnZero { condition, target } => {
-				// mov rax, [rsp + condition * 8]
-				self.emit([0x48, 0x8B, 0x84, 0x24]);
-				self.emit(((condition.0 * 8) as u32).to_ne_bytes());
+				self.load(Register::Rax, condition);
 
-				// test rax, rax
-				self.emit([0x48, 0x85, 0xC0]);
+				self.assembler.test(Register::Rax, Register::Rax);
+				self.assembler.jcc(Condition::Equal, self.nodes[target]);
+			}
+			llir::Node::JumpOnNonZero { condition, target } => {
+				self.load(Register::Rax, condition);
 
-				// jz target
-				self.emit([0x0F, 0x84]);
+				self.assembler.test(Register::Rax, Register::Rax);
+				self.assembler.jcc(Condition::NotEqual, self.nodes[target]);
+			}
+			llir::Node::JumpUnless {
+				comparison,
+				lhs,
+				rhs,
+				signed,
+				target,
+			} => {
+				self.load(Register::Rax, lhs);
+				self.load(Register::Rcx, rhs);
 
-				let pos = self.output.len();
-				self.emit([0x00, 0x00, 0x00, 0x00]);
+				self.assembler.cmp(Register::Rax, Register::Rcx);
 
-				self.branch_fixups.push((pos, target));
+				// the opposite condition, since the jump skips what follows
+				let condition = Self::condition(comparison, signed).negate();
+				self.assembler.jcc(condition, self.nodes[target]);
 			}
 			llir::Node::Add { dst, lhs, rhs } => {
-				// mov rax, [rsp + lhs * 8]
-				self.emit([0x48, 0x8B, 0x84, 0x24]);
-				self.emit(((lhs.0 * 8) as u32).to_ne_bytes());
+				self.load(Register::Rax, lhs);
+				self.load(Register::Rcx, rhs);
+
+				self.assembler.add(Register::Rax, Register::Rcx);
+
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Sub { dst, lhs, rhs } => {
+				self.load(Register::Rax, lhs);
+				self.load(Register::Rcx, rhs);
+
+				self.assembler.sub(Register::Rax, Register::Rcx);
 
-				// mov rcx, [rsp + rhs * 8]
-				self.emit([0x48, 0x8B, 0x8C, 0x24]);
-				self.emit(((rhs.0 * 8) as u32).to_ne_bytes());
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Mul {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				self.load(Register::Rax, lhs);
+				self.load(Register::Rcx, rhs);
 
-				// add rax, rcx
-				self.emit([0x48, 0x01, 0xC8]);
+				if signed {
+					self.assembler.imul(Register::Rax, Register::Rcx);
+				} else {
+					self.assembler.mul(Register::Rcx);
+				}
 
-				// mov [rsp + dst * 8], rax
-				self.emit([0x48, 0x89, 0x84, 0x24]);
-				self.emit(((dst.0 * 8) as u32).to_ne_bytes());
+				self.store(dst, Register::Rax);
 			}
-			llir::Node::Mul { dst, lhs, rhs } => {
-				// mov rax, [rsp + lhs * 8]
-				self.emit([0x48, 0x8B, 0x84, 0x24]);
-				self.emit(((lhs.0 * 8) as u32).to_ne_bytes());
+			llir::Node::Div {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				self.load(Register::Rax, lhs);
+				self.load(Register::Rcx, rhs);
 
-				// mov rcx, [rsp + rhs * 8]
-				self.emit([0x48, 0x8B, 0x8C, 0x24]);
-				self.emit(((rhs.0 * 8) as u32).to_ne_bytes());
+				self.emit_divide(signed);
+
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Mod {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				self.load(Register::Rax, lhs);
+				self.load(Register::Rcx, rhs);
 
-				// mul rcx
-				self.emit([0x48, 0xF7, 0xE1]);
+				self.emit_divide(signed);
 
-				// mov [rsp + dst * 8], rax
-				self.emit([0x48, 0x89, 0x84, 0x24]);
-				self.emit(((dst.0 * 8) as u32).to_ne_bytes());
+				self.store(dst, Register::Rdx);
 			}
 			llir::Node::Equals { dst, lhs, rhs } => {
-				// mov rax, [rsp + lhs * 8]
-				self.emit([0x48, 0x8B, 0x84, 0x24]);
-				self.emit(((lhs.0 * 8) as u32).to_ne_bytes());
+				self.compile_comparison(dst, lhs, rhs, Condition::Equal);
+			}
+			llir::Node::NotEquals { dst, lhs, rhs } => {
+				self.compile_comparison(dst, lhs, rhs, Condition::NotEqual);
+			}
+			llir::Node::Less {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				let condition = Self::condition(llir::Comparison::Less, signed);
+				self.compile_comparison(dst, lhs, rhs, condition);
+			}
+			llir::Node::LessEquals {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				let condition = Self::condition(llir::Comparison::LessEqual, signed);
+				self.compile_comparison(dst, lhs, rhs, condition);
+			}
+			llir::Node::Greater {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				let condition = Self::condition(llir::Comparison::Greater, signed);
+				self.compile_comparison(dst, lhs, rhs, condition);
+			}
+			llir::Node::GreaterEquals {
+				dst,
+				lhs,
+				rhs,
+				signed,
+			} => {
+				let condition = Self::condition(llir::Comparison::GreaterEqual, signed);
+				self.compile_comparison(dst, lhs, rhs, condition);
+			}
+			llir::Node::Negate { dst, value } => {
+				self.load(Register::Rax, value);
 
-				// mov rcx, [rsp + rhs * 8]
-				self.emit([0x48, 0x8B, 0x8C, 0x24]);
-				self.emit(((rhs.0 * 8) as u32).to_ne_bytes());
+				self.assembler.neg(Register::Rax);
+
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Not { dst, value } => {
+				self.load(Register::Rax, value);
 
-				// cmp rax, rcx
-				self.emit([0x48, 0x39, 0xC8]);
+				self.assembler.xor(Register::Rax, 1);
 
-				// sete al
-				self.emit([0x0F, 0x94, 0xC0]);
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Extend {
+				dst,
+				value,
+				size,
+				signed,
+			} => {
+				self.load(Register::Rax, value);
 
-				// movzx rax, al
-				self.emit([0x48, 0x0F, 0xB6, 0xC0]);
+				if signed {
+					self.assembler.movsx(Register::Rax, Register::Rax, size);
+				} else {
+					self.assembler.movzx(Register::Rax, Register::Rax, size);
+				}
 
-				// mov [rsp + dst * 8], rax
-				self.emit([0x48, 0x89, 0x84, 0x24]);
-				self.emit(((dst.0 * 8) as u32).to_ne_bytes());
+				self.store(dst, Register::Rax);
 			}
 			llir::Node::Return { value } => {
 				if let Some(value) = value {
-					// mov rax, [rsp + lhs * 8]
-					self.emit([0x48, 0x8B, 0x84, 0x24]);
-					self.emit(((value.0 * 8) as u32).to_ne_bytes());
+					self.load(Register::Rax, value);
 				}
 
-				// mov rsp, rbp
-				self.emit([0x48, 0x89, 0xEC]);
+				for (register, slot) in self.allocation.saved.clone() {
+					self.load_slot(register, slot);
+				}
+
+				self.assembler.mov(Register::Rsp, Register::Rbp);
+				self.assembler.pop(Register::Rbp);
+				self.assembler.ret();
+			}
+			llir::Node::Print { dst, value } => {
+				self.load_argument(1, value);
+
+				// the length is stored right before the string
+				self.assembler
+					.mov(Register::Rdx, Memory::new(Register::Rsi, -8));
+				self.assembler.mov(Register::Rdi, 1);
 
-				// pop rbp
-				self.emit([0x5D]);
+				self.emit_syscall(SYS_WRITE);
 
-				// ret
-				self.emit([0xC3]);
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::PrintI64 { dst, value } => {
+				self.compile_print_i64(value);
+
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Write {
+				dst,
+				fd,
+				buffer,
+				length,
+			} => {
+				self.load_argument(0, fd);
+				self.load_argument(1, buffer);
+				self.load_argument(2, length);
+
+				self.emit_syscall(SYS_WRITE);
+
+				self.store(dst, Register::Rax);
+			}
+			llir::Node::Exit { code } => {
+				self.load_argument(0, code);
+				self.emit_syscall(SYS_EXIT);
 			}
 			llir::Node::Call {
 				dst,
 				function,
 				arguments,
 			} => {
-				for (index, register) in arguments.iter().enumerate() {
-					match index {
-						0 => {
-							// mov rdi, [rsp + lhs * 8]
-							self.emit([0x48, 0x8B, 0xBC, 0x24]);
-							self.emit(((register.0 * 8) as u32).to_ne_bytes());
-						}
-						_ => todo!(),
-					}
+				let stack_arguments = arguments.get(ARGUMENT_REGISTERS.len()..).unwrap_or(&[]);
+
+				// the frame keeps `rsp` aligned, so only an odd number of
+				// stack arguments needs padding
+				let padding = stack_arguments.len() % 2 * 8;
+				let pushed = padding + stack_arguments.len() * 8;
+
+				if padding != 0 {
+					self.assembler.sub(Register::Rsp, padding as i32);
+				}
+
+				for register in stack_arguments.iter().rev() {
+					self.load(Register::Rax, *register);
+					self.assembler.push(Register::Rax);
 				}
 
-				// call function
-				self.emit([0xE8]);
+				for (register, argument) in arguments.iter().zip(ARGUMENT_REGISTERS) {
+					self.load(argument, *register);
+				}
 
-				let pos = self.output.len();
-				self.emit([0x00, 0x00, 0x00, 0x00]);
+				let position = self.assembler.call(self.functions[function]);
+				self.function_fixups.push((position, function));
 
-				self.function_fixups.push((pos, function));
+				if pushed != 0 {
+					self.assembler.add(Register::Rsp, pushed as i32);
+				}
 
-				// mov [rsp + dst * 8], rax
-				self.emit([0x48, 0x89, 0x84, 0x24]);
-				self.emit(((dst.0 * 8) as u32).to_ne_bytes());
+				self.store(dst, Register::Rax);
 			}
 		}
 	}
 
-	fn emit<const N: usize>(&mut self, bytes: [u8; N]) {
-		self.output.extend(bytes);
+	fn condition(comparison: llir::Comparison, signed: bool) -> Condition {
+		match (comparison, signed) {
+			(llir::Comparison::Equal, _) => Condition::Equal,
+			(llir::Comparison::NotEqual, _) => Condition::NotEqual,
+			(llir::Comparison::Less, true) => Condition::Less,
+			(llir::Comparison::Less, false) => Condition::Below,
+			(llir::Comparison::LessEqual, true) => Condition::LessEqual,
+			(llir::Comparison::LessEqual, false) => Condition::BelowEqual,
+			(llir::Comparison::Greater, true) => Condition::Greater,
+			(llir::Comparison::Greater, false) => Condition::Above,
+			(llir::Comparison::GreaterEqual, true) => Condition::GreaterEqual,
+			(llir::Comparison::GreaterEqual, false) => Condition::AboveEqual,
+		}
+	}
+
+	fn compile_comparison(
+		&mut self,
+		dst: llir::Register,
+		lhs: llir::Register,
+		rhs: llir::Register,
+		condition: Condition,
+	) {
+		self.load(Register::Rax, lhs);
+		self.load(Register::Rcx, rhs);
+
+		self.assembler.cmp(Register::Rax, Register::Rcx);
+		self.assembler.setcc(condition, Register::Rax);
+		self.assembler.movzx(Register::Rax, Register::Rax, 1);
+
+		self.store(dst, Register::Rax);
+	}
+
+	/// Divides `rax` by `rcx`, leaving the quotient in `rax` and the remainder
+	/// in `rdx`.
+	fn emit_divide(&mut self, signed: bool) {
+		if signed {
+			self.assembler.cqo();
+			self.assembler.idiv(Register::Rcx);
+		} else {
+			self.assembler.xor(Register::Rdx, Register::Rdx);
+			self.assembler.div(Register::Rcx);
+		}
+	}
+
+	/// Converts `value` to decimal in a buffer below the stack pointer and
+	/// writes it to stdout, followed by a newline.
+	fn compile_print_i64(&mut self, value: llir::Register) {
+		let end = Memory::new(Register::Rsp, 32);
+		let cursor = Memory::new(Register::Rsi, 0);
+
+		self.load(Register::Rax, value);
+
+		self.assembler.sub(Register::Rsp, 32);
+
+		// the digits are written backwards from the end of the buffer
+		self.assembler.lea(Register::Rsi, end);
+
+		self.assembler.dec(Register::Rsi);
+		self.assembler.mov_byte(cursor, b'\n');
+
+		self.assembler.mov(Register::R8, Register::Rax);
+		self.assembler.mov(Register::Rcx, 10);
+
+		// the magnitude of i64::MIN only fits when treated as unsigned, which
+		// is how `div` sees it
+		let positive = self.assembler.label();
+		self.assembler.test(Register::Rax, Register::Rax);
+		self.assembler.jcc(Condition::NotSign, positive);
+		self.assembler.neg(Register::Rax);
+		self.assembler.bind(positive);
+
+		let digit = self.assembler.label();
+		self.assembler.bind(digit);
+
+		self.assembler.xor(Register::Rdx, Register::Rdx);
+		self.assembler.div(Register::Rcx);
+
+		self.assembler.add(Register::Rdx, b'0');
+		self.assembler.dec(Register::Rsi);
+		self.assembler.mov_byte(cursor, Register::Rdx);
+
+		self.assembler.test(Register::Rax, Register::Rax);
+		self.assembler.jcc(Condition::NotEqual, digit);
+
+		let done = self.assembler.label();
+		self.assembler.test(Register::R8, Register::R8);
+		self.assembler.jcc(Condition::NotSign, done);
+		self.assembler.dec(Register::Rsi);
+		self.assembler.mov_byte(cursor, b'-');
+		self.assembler.bind(done);
+
+		self.assembler.lea(Register::Rdx, end);
+		self.assembler.sub(Register::Rdx, Register::Rsi);
+		self.assembler.mov(Register::Rdi, 1);
+
+		self.emit_syscall(SYS_WRITE);
+
+		self.assembler.add(Register::Rsp, 32);
+	}
+
+	/// Loads `register` into the `index`th System V argument register, which
+	/// are also the first syscall arguments.
+	fn load_argument(&mut self, index: usize, register: llir::Register) {
+		self.load(ARGUMENT_REGISTERS[index], register);
+	}
+
+	fn load(&mut self, dst: Register, register: llir::Register) {
+		match self.allocation.location(register) {
+			Location::Register(src) => self.move_register(dst, src),
+			Location::Stack(slot) => self.load_slot(dst, slot),
+		}
+	}
+
+	fn store(&mut self, register: llir::Register, src: Register) {
+		match self.allocation.location(register) {
+			Location::Register(dst) => self.move_register(dst, src),
+			Location::Stack(slot) => self.store_slot(slot, src),
+		}
+	}
+
+	fn move_register(&mut self, dst: Register, src: Register) {
+		if dst != src {
+			self.assembler.mov(dst, src);
+		}
+	}
+
+	fn load_slot(&mut self, dst: Register, slot: usize) {
+		self.assembler.mov(dst, self.frame.slot(slot));
+	}
+
+	fn store_slot(&mut self, slot: usize, src: Register) {
+		self.assembler.mov(self.frame.slot(slot), src);
+	}
+
+	fn emit_syscall(&mut self, number: u32) {
+		self.assembler.mov(Register::Rax, i64::from(number));
+		self.assembler.syscall();
 	}
 
-	fn encode_relative_32(&mut self, position: usize, target: usize) {
-		let target = (std::num::Wrapping(target) - std::num::Wrapping(position + 4)).0;
+	/// Adds `value` to `rodata` unless it's already there, returning the
+	/// offset of its first byte. The length comes right before that as a
+	/// 64-bit integer, and a NUL right after so C can read it too.
+	fn intern(&mut self, value: String) -> usize {
+		if let Some(offset) = self.strings.get(&value) {
+			return *offset;
+		}
+
+		self.rodata.resize(self.rodata.len().next_multiple_of(8), 0);
+		self.rodata.extend((value.len() as u64).to_le_bytes());
+
+		let offset = self.rodata.len();
+		self.rodata.extend(value.as_bytes());
+		self.rodata.push(0);
 
-		*self.output.get_mut(position + 0).unwrap() = ((target >> 00) & 0xFF) as u8;
-		*self.output.get_mut(position + 1).unwrap() = ((target >> 08) & 0xFF) as u8;
-		*self.output.get_mut(position + 2).unwrap() = ((target >> 16) & 0xFF) as u8;
-		*self.output.get_mut(position + 3).unwrap() = ((target >> 24) & 0xFF) as u8;
+		self.strings.insert(value, offset);
+		offset
 	}
 }
@@ -0,0 +1,695 @@
+use crate::x86_64::Program;
+
+const REGISTERS_64: [&str; 16] = [
+	"rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12", "r13",
+	"r14", "r15",
+];
+
+const REGISTERS_32: [&str; 16] = [
+	"eax", "ecx", "edx", "ebx", "esp", "ebp", "esi", "edi", "r8d", "r9d", "r10d", "r11d", "r12d",
+	"r13d", "r14d", "r15d",
+];
+
+const REGISTERS_16: [&str; 16] = [
+	"ax", "cx", "dx", "bx", "sp", "bp", "si", "di", "r8w", "r9w", "r10w", "r11w", "r12w", "r13w",
+	"r14w", "r15w",
+];
+
+const REGISTERS_8: [&str; 16] = [
+	"al", "cl", "dl", "bl", "spl", "bpl", "sil", "dil", "r8b", "r9b", "r10b", "r11b", "r12b",
+	"r13b", "r14b", "r15b",
+];
+
+/// Condition codes in the order of the low nibble of `jcc` and `setcc`.
+const CONDITIONS: [&str; 16] = [
+	"o", "no", "b", "ae", "e", "ne", "be", "a", "s", "ns", "p", "np", "l", "ge", "le", "g",
+];
+
+/// Mnemonics of the `0x81` and `0x83` groups, picked by the ModRM reg field.
+const ARITHMETIC: [&str; 8] = ["add", "or", "adc", "sbb", "and", "sub", "xor", "cmp"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Size {
+	Byte,
+	Word,
+	Dword,
+	Qword,
+}
+
+impl Size {
+	fn register(self, index: u8) -> &'static str {
+		let names = match self {
+			Size::Byte => &REGISTERS_8,
+			Size::Word => &REGISTERS_16,
+			Size::Dword => &REGISTERS_32,
+			Size::Qword => &REGISTERS_64,
+		};
+
+		names[index as usize]
+	}
+
+	fn name(self) -> &'static str {
+		match self {
+			Size::Byte => "byte",
+			Size::Word => "word",
+			Size::Dword => "dword",
+			Size::Qword => "qword",
+		}
+	}
+}
+
+/// The r/m operand of a ModRM byte.
+enum Operand {
+	Register(u8),
+	Memory(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instruction {
+	pub offset: usize,
+	pub length: usize,
+	pub text: String,
+	/// Where a jump or call goes, as an offset into the code.
+	pub target: Option<usize>,
+	/// Where the `disp32` of a RIP-relative operand is, and the offset into
+	/// the code that it points at.
+	pub rip_relative: Option<(usize, usize)>,
+}
+
+struct Decoder<'a> {
+	code: &'a [u8],
+	start: usize,
+	position: usize,
+	rex: u8,
+	/// Position of a RIP-relative `disp32`, if the instruction has one.
+	rip_displacement: Option<usize>,
+}
+
+impl Decoder<'_> {
+	fn byte(&mut self) -> Option<u8> {
+		let ret = *self.code.get(self.position)?;
+		self.position += 1;
+
+		Some(ret)
+	}
+
+	fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+		let ret = self.code.get(self.position..self.position + N)?;
+		self.position += N;
+
+		ret.try_into().ok()
+	}
+
+	fn imm8(&mut self) -> Option<i8> {
+		Some(self.byte()? as i8)
+	}
+
+	fn imm32(&mut self) -> Option<i32> {
+		Some(i32::from_le_bytes(self.bytes()?))
+	}
+
+	fn operand_size(&self) -> Size {
+		if self.rex & 0x08 != 0 {
+			Size::Qword
+		} else {
+			Size::Dword
+		}
+	}
+
+	/// Decodes a ModRM byte and whatever SIB byte or displacement follows,
+	/// returning the reg field and the r/m operand.
+	fn modrm(&mut self) -> Option<(u8, Operand)> {
+		let modrm = self.byte()?;
+
+		let mode = modrm >> 6;
+		let reg = (modrm >> 3 & 7) | (self.rex & 0x04) << 1;
+		let rm = modrm & 7;
+
+		if mode == 3 {
+			return Some((reg, Operand::Register(rm | (self.rex & 0x01) << 3)));
+		}
+
+		if mode == 0 && rm == 5 {
+			self.rip_displacement = Some(self.position);
+			let displacement = self.imm32()?;
+
+			return Some((reg, Operand::Memory(Self::address("rip", displacement))));
+		}
+
+		let base = if rm == 4 {
+			let sib = self.byte()?;
+			let index = (sib >> 3 & 7) | (self.rex & 0x02) << 2;
+
+			// only `[base + disp]` is ever emitted
+			if index != 4 || sib & 7 == 5 && mode == 0 {
+				return None;
+			}
+
+			sib & 7 | (self.rex & 0x01) << 3
+		} else {
+			rm | (self.rex & 0x01) << 3
+		};
+
+		let displacement = match mode {
+			0 => 0,
+			1 => self.imm8()? as i32,
+			_ => self.imm32()?,
+		};
+
+		let base = REGISTERS_64[base as usize];
+		Some((reg, Operand::Memory(Self::address(base, displacement))))
+	}
+
+	fn address(base: &str, displacement: i32) -> String {
+		match displacement {
+			0 => format!("[{base}]"),
+			x if x < 0 => format!("[{base} - {:#x}]", x.unsigned_abs()),
+			x => format!("[{base} + {x:#x}]"),
+		}
+	}
+
+	fn immediate(value: i64) -> String {
+		if value < 0 {
+			format!("-{:#x}", value.unsigned_abs())
+		} else {
+			format!("{value:#x}")
+		}
+	}
+
+	/// Formats `operand` as `size`, naming the size for memory when nothing
+	/// else in the instruction gives it away.
+	fn show(operand: &Operand, size: Size, explicit: bool) -> String {
+		match operand {
+			Operand::Register(x) => size.register(*x).into(),
+			Operand::Memory(x) if explicit => format!("{} {x}", size.name()),
+			Operand::Memory(x) => x.clone(),
+		}
+	}
+
+	fn relative(&self, displacement: i32) -> usize {
+		(self.position as isize + displacement as isize) as usize
+	}
+
+	fn decode(&mut self) -> Option<(String, Option<usize>)> {
+		let mut opcode = self.byte()?;
+
+		if opcode & 0xF0 == 0x40 {
+			self.rex = opcode;
+			opcode = self.byte()?;
+		}
+
+		let size = self.operand_size();
+
+		let text = match opcode {
+			0x01 | 0x29 | 0x31 | 0x39 | 0x85 | 0x89 => {
+				let mnemonic = match opcode {
+					0x85 => "test",
+					0x89 => "mov",
+					_ => ARITHMETIC[opcode as usize >> 3],
+				};
+
+				let (reg, rm) = self.modrm()?;
+				let rm = Self::show(&rm, size, false);
+
+				format!("{mnemonic} {rm}, {}", size.register(reg))
+			}
+			0x03 | 0x2B | 0x33 | 0x3B | 0x8B | 0x8D => {
+				let mnemonic = match opcode {
+					0x8B => "mov",
+					0x8D => "lea",
+					_ => ARITHMETIC[opcode as usize >> 3],
+				};
+
+				let (reg, rm) = self.modrm()?;
+
+				format!(
+					"{mnemonic} {}, {}",
+					size.register(reg),
+					Self::show(&rm, size, false)
+				)
+			}
+			0x88 => {
+				let (reg, rm) = self.modrm()?;
+				let rm = Self::show(&rm, Size::Byte, false);
+
+				format!("mov {rm}, {}", Size::Byte.register(reg))
+			}
+			0x63 => {
+				let (reg, rm) = self.modrm()?;
+
+				format!(
+					"movsxd {}, {}",
+					size.register(reg),
+					Self::show(&rm, Size::Dword, false)
+				)
+			}
+			0x50..=0x57 => {
+				let register = (opcode - 0x50) | (self.rex & 0x01) << 3;
+				format!("push {}", REGISTERS_64[register as usize])
+			}
+			0x58..=0x5F => {
+				let register = (opcode - 0x58) | (self.rex & 0x01) << 3;
+				format!("pop {}", REGISTERS_64[register as usize])
+			}
+			0x70..=0x7F => {
+				let displacement = self.imm8()? as i32;
+				let target = self.relative(displacement);
+
+				return Some((
+					format!("j{} {target:#x}", CONDITIONS[opcode as usize & 0xF]),
+					Some(target),
+				));
+			}
+			0x80 | 0x83 => {
+				let (reg, rm) = self.modrm()?;
+				let size = if opcode == 0x80 { Size::Byte } else { size };
+				let imm = self.imm8()?;
+
+				format!(
+					"{} {}, {}",
+					ARITHMETIC[reg as usize & 7],
+					Self::show(&rm, size, true),
+					Self::immediate(imm.into())
+				)
+			}
+			0x81 => {
+				let (reg, rm) = self.modrm()?;
+				let imm = self.imm32()?;
+
+				format!(
+					"{} {}, {}",
+					ARITHMETIC[reg as usize & 7],
+					Self::show(&rm, size, true),
+					Self::immediate(imm.into())
+				)
+			}
+			0x99 if size == Size::Qword => "cqo".into(),
+			0x99 => "cdq".into(),
+			0xB8..=0xBF => {
+				let register = (opcode - 0xB8) | (self.rex & 0x01) << 3;
+
+				let imm = if size == Size::Qword {
+					i64::from_le_bytes(self.bytes()?)
+				} else {
+					self.imm32()? as u32 as i64
+				};
+
+				format!("mov {}, {}", size.register(register), Self::immediate(imm))
+			}
+			0xC3 => "ret".into(),
+			0xC6 => {
+				let (reg, rm) = self.modrm()?;
+
+				if reg & 7 != 0 {
+					return None;
+				}
+
+				let imm = self.byte()?;
+				format!("mov {}, {imm:#x}", Self::show(&rm, Size::Byte, true))
+			}
+			0xC7 => {
+				let (reg, rm) = self.modrm()?;
+
+				if reg & 7 != 0 {
+					return None;
+				}
+
+				let imm = self.imm32()?;
+				format!(
+					"mov {}, {}",
+					Self::show(&rm, size, true),
+					Self::immediate(imm.into())
+				)
+			}
+			0xE8 | 0xE9 => {
+				let displacement = self.imm32()?;
+				let target = self.relative(displacement);
+				let mnemonic = if opcode == 0xE8 { "call" } else { "jmp" };
+
+				return Some((format!("{mnemonic} {target:#x}"), Some(target)));
+			}
+			0xEB => {
+				let displacement = self.imm8()? as i32;
+				let target = self.relative(displacement);
+
+				return Some((format!("jmp {target:#x}"), Some(target)));
+			}
+			0xF7 => {
+				let (reg, rm) = self.modrm()?;
+
+				let mnemonic = match reg & 7 {
+					2 => "not",
+					3 => "neg",
+					4 => "mul",
+					5 => "imul",
+					6 => "div",
+					7 => "idiv",
+					_ => return None,
+				};
+
+				format!("{mnemonic} {}", Self::show(&rm, size, true))
+			}
+			0xFF => {
+				let (reg, rm) = self.modrm()?;
+
+				let mnemonic = match reg & 7 {
+					0 => "inc",
+					1 => "dec",
+					_ => return None,
+				};
+
+				format!("{mnemonic} {}", Self::show(&rm, size, true))
+			}
+			0x0F => return self.decode_two_byte(size),
+			_ => return None,
+		};
+
+		Some((text, None))
+	}
+
+	fn decode_two_byte(&mut self, size: Size) -> Option<(String, Option<usize>)> {
+		let opcode = self.byte()?;
+
+		let text = match opcode {
+			0x05 => "syscall".into(),
+			0x80..=0x8F => {
+				let displacement = self.imm32()?;
+				let target = self.relative(displacement);
+
+				return Some((
+					format!("j{} {target:#x}", CONDITIONS[opcode as usize & 0xF]),
+					Some(target),
+				));
+			}
+			0x90..=0x9F => {
+				let (_, rm) = self.modrm()?;
+
+				format!(
+					"set{} {}",
+					CONDITIONS[opcode as usize & 0xF],
+					Self::show(&rm, Size::Byte, true)
+				)
+			}
+			0xAF => {
+				let (reg, rm) = self.modrm()?;
+
+				format!(
+					"imul {}, {}",
+					size.register(reg),
+					Self::show(&rm, size, false)
+				)
+			}
+			0xB6 | 0xB7 | 0xBE | 0xBF => {
+				let (reg, rm) = self.modrm()?;
+
+				let mnemonic = if opcode < 0xBE { "movzx" } else { "movsx" };
+				let from = if opcode & 1 == 0 {
+					Size::Byte
+				} else {
+					Size::Word
+				};
+
+				format!(
+					"{mnemonic} {}, {}",
+					size.register(reg),
+					Self::show(&rm, from, true)
+				)
+			}
+			_ => return None,
+		};
+
+		Some((text, None))
+	}
+}
+
+/// Decodes the instruction at `offset` in `code`. Bytes that aren't part of
+/// anything the compiler emits come out as a one byte `(bad)` instruction.
+pub fn decode(code: &[u8], offset: usize) -> Instruction {
+	let mut decoder = Decoder {
+		code,
+		start: offset,
+		position: offset,
+		rex: 0,
+		rip_displacement: None,
+	};
+
+	match decoder.decode() {
+		Some((text, target)) => {
+			let rip_relative = decoder.rip_displacement.map(|x| {
+				let displacement = i32::from_le_bytes(code[x..x + 4].try_into().unwrap());
+				(x, decoder.relative(displacement))
+			});
+
+			Instruction {
+				offset,
+				length: decoder.position - decoder.start,
+				text,
+				target,
+				rip_relative,
+			}
+		}
+		None => Instruction {
+			offset,
+			length: 1,
+			text: "(bad)".into(),
+			target: None,
+			rip_relative: None,
+		},
+	}
+}
+
+/// Decodes all of `code`.
+pub fn decode_all(code: &[u8]) -> Vec<Instruction> {
+	let mut ret = Vec::new();
+	let mut offset = 0;
+
+	while offset < code.len() {
+		let instruction = decode(code, offset);
+		offset += instruction.length;
+
+		ret.push(instruction);
+	}
+
+	ret
+}
+
+/// Lists the code of `program` one instruction per line, with its offset
+/// and bytes, a header at the start of every function, jump and call targets
+/// named after the function they land in, and the string that every
+/// RIP-relative operand loads.
+pub fn disassemble(program: &Program) -> String {
+	let mut ret = String::new();
+
+	let name = |offset: usize| {
+		let symbol = program
+			.symbols
+			.iter()
+			.find(|x| (x.offset..x.offset + x.size).contains(&offset))?;
+
+		Some(match offset - symbol.offset {
+			0 => format!("<{}>", symbol.name),
+			x => format!("<{}+{x:#x}>", symbol.name),
+		})
+	};
+
+	for instruction in decode_all(&program.code) {
+		if let Some(symbol) = program
+			.symbols
+			.iter()
+			.find(|x| x.offset == instruction.offset)
+		{
+			if !ret.is_empty() {
+				ret.push('\n');
+			}
+
+			ret += &format!("{:016x} <{}>:\n", symbol.offset, symbol.name);
+		}
+
+		let bytes = program.code[instruction.offset..instruction.offset + instruction.length]
+			.iter()
+			.map(|x| format!("{x:02x}"))
+			.collect::<Vec<_>>()
+			.join(" ");
+
+		ret += &format!(
+			"{:8x}:\t{bytes:<30}\t{}",
+			instruction.offset, instruction.text
+		);
+
+		if let Some(name) = instruction.target.and_then(name) {
+			ret += &format!(" {name}");
+		}
+
+		// these are relocated when the program is loaded, so the code only
+		// has zeros where they go
+		let data = instruction.rip_relative.and_then(|(position, _)| {
+			program
+				.data_fixups
+				.iter()
+				.find(|(x, _)| *x == position)
+				.map(|(_, offset)| *offset)
+		});
+
+		if let Some(offset) = data {
+			ret += &format!("\t; rodata+{offset:#x}");
+
+			let length = program
+				.rodata
+				.get(offset.wrapping_sub(8)..offset)
+				.map(|x| u64::from_le_bytes(x.try_into().unwrap()) as usize);
+
+			if let Some(x) = length.and_then(|x| program.rodata.get(offset..offset + x)) {
+				ret += &format!(" {:?}", String::from_utf8_lossy(x));
+			}
+		}
+
+		ret.push('\n');
+	}
+
+	ret
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{hlir, x86_64::Compiler, Lexer, Lowerer, Parser, SourceMap};
+
+	fn compile(source: &str, spill_all: bool) -> Program {
+		let mut sources = SourceMap::new();
+		let file = sources.add("test.y", source);
+
+		let tokens = Lexer::new(&sources, file).lex().unwrap();
+		let (ast, diagnostics) = Parser::new(tokens).parse_global_scope();
+		assert!(diagnostics.is_empty());
+
+		let mut ctx = hlir::Context::new();
+		ast.define_functions(&mut ctx).unwrap();
+
+		let mut lowerer = Lowerer::new();
+		lowerer.lower(ast.generate(&mut ctx).unwrap());
+
+		Compiler::new()
+			.with_spill_all(spill_all)
+			.compile(lowerer.get())
+			.unwrap()
+	}
+
+	#[test]
+	fn decodes_known_encodings() {
+		let cases: &[(&[u8], &str)] = &[
+			(&[0x55], "push rbp"),
+			(&[0x48, 0x89, 0xE5], "mov rbp, rsp"),
+			(&[0x48, 0x81, 0xEC, 0x10, 0, 0, 0], "sub rsp, 0x10"),
+			(
+				&[0x48, 0x8B, 0x84, 0x24, 8, 0, 0, 0],
+				"mov rax, [rsp + 0x8]",
+			),
+			(&[0x4C, 0x89, 0xA4, 0x24, 0, 0, 0, 0], "mov [rsp], r12"),
+			(&[0x48, 0x8B, 0x56, 0xF8], "mov rdx, [rsi - 0x8]"),
+			(&[0x48, 0x8B, 0x85, 0x10, 0, 0, 0], "mov rax, [rbp + 0x10]"),
+			(&[0x48, 0x8D, 0x74, 0x24, 0x20], "lea rsi, [rsp + 0x20]"),
+			(&[0x48, 0xB8, 1, 0, 0, 0, 0, 0, 0, 0], "mov rax, 0x1"),
+			(&[0xBF, 1, 0, 0, 0], "mov edi, 0x1"),
+			(&[0x48, 0xC7, 0xC0, 0xFF, 0xFF, 0xFF, 0xFF], "mov rax, -0x1"),
+			(&[0x48, 0x2B, 0x45, 0xF0], "sub rax, [rbp - 0x10]"),
+			(&[0x48, 0x0F, 0xAF, 0xC1], "imul rax, rcx"),
+			(&[0x48, 0xF7, 0xF9], "idiv rcx"),
+			(&[0x48, 0x99], "cqo"),
+			(&[0x0F, 0x9C, 0xC0], "setl al"),
+			(&[0x48, 0x0F, 0xBE, 0xC0], "movsx rax, al"),
+			(&[0x0F, 0xB7, 0xC0], "movzx eax, ax"),
+			(&[0x48, 0x63, 0xC0], "movsxd rax, eax"),
+			(&[0x89, 0xC0], "mov eax, eax"),
+			(&[0x48, 0x83, 0xF0, 0x01], "xor rax, 0x1"),
+			(&[0xC6, 0x06, 0x0A], "mov byte [rsi], 0xa"),
+			(&[0x80, 0xC2, 0x30], "add dl, 0x30"),
+			(&[0x88, 0x16], "mov [rsi], dl"),
+			(&[0x48, 0xFF, 0xCE], "dec rsi"),
+			(&[0x4D, 0x85, 0xC0], "test r8, r8"),
+			(&[0x0F, 0x05], "syscall"),
+			(&[0x5D], "pop rbp"),
+			(&[0xC3], "ret"),
+			(&[0x0F, 0x0B], "(bad)"),
+		];
+
+		for (bytes, text) in cases {
+			let instruction = decode(bytes, 0);
+
+			assert_eq!(instruction.text, *text, "{bytes:02X?}");
+
+			if *text != "(bad)" {
+				assert_eq!(instruction.length, bytes.len(), "{text}");
+			}
+		}
+	}
+
+	#[test]
+	fn resolves_targets() {
+		// jz +2, jmp -8, call +0
+		let code = [
+			0x0F, 0x84, 0x02, 0x00, 0x00, 0x00, 0xEB, 0xF8, 0xE8, 0x00, 0x00, 0x00, 0x00,
+		];
+
+		let instructions = decode_all(&code);
+		let targets = instructions.iter().map(|x| x.target).collect::<Vec<_>>();
+
+		assert_eq!(targets, [Some(8), Some(0), Some(13)]);
+		assert_eq!(instructions[0].text, "je 0x8");
+	}
+
+	#[test]
+	fn decodes_everything_the_compiler_emits() {
+		let source = "
+			fn fib(n: u32) -> u32 {
+				if n < 2 || n == 1000 {
+					return n;
+				}
+
+				return fib(n - 1) + fib(n - 2);
+			}
+
+			fn many(a: i8, b: i16, c: i32, d: i64, e: u8, f: u16, g: u64, h: bool) -> i64 {
+				let x = a as i64 * b as i64 / c as i64 % d;
+				let y = -x + (e as i64) - (f as i64) + (g as i64);
+
+				if h && !(x >= y) && x <= y && x > 0 {
+					return x;
+				}
+
+				return y;
+			}
+
+			fn main() -> i64 {
+				let mut i = 0;
+
+				while i != 10 {
+					i += 1;
+				}
+
+				print(\"hello\\n\");
+				print_i64(fib(10) as i64);
+				write(1, \"x\", 1);
+
+				let b = many(1, 2, 3, 4, 5, 6, 7, true) > 0;
+				if b {
+					exit(3);
+				}
+
+				return i;
+			}
+		";
+
+		for spill_all in [false, true] {
+			let program = compile(source, spill_all);
+			let listing = disassemble(&program);
+
+			assert!(!listing.contains("(bad)"), "{listing}");
+
+			for symbol in ["<fib>:", "<many>:", "<main>:", "call 0x0 <fib>"] {
+				assert!(listing.contains(symbol), "missing {symbol} in\n{listing}");
+			}
+
+			assert!(listing.contains("; rodata+0x8 \"hello\\n\""), "{listing}");
+		}
+	}
+}
@@ -1,3 +1,8 @@
+use crate::{
+	x86_64::{self, SYS_MMAP, SYS_MPROTECT, SYS_MUNMAP},
+	Diagnostic, Result,
+};
+
 unsafe fn syscall(
 	id: usize,
 	a0: usize,
@@ -25,13 +30,25 @@ unsafe fn syscall(
 	ret
 }
 
+const PAGE_SIZE: usize = 0x1000;
+
+const PROT_READ: usize = 1;
+const PROT_WRITE: usize = 2;
+const PROT_EXEC: usize = 4;
+
 fn mmap(size: usize) -> *mut u8 {
-	unsafe { syscall(9, 0, size, 7, 0x22, 0, 0) as _ }
+	unsafe { syscall(SYS_MMAP as _, 0, size, PROT_READ | PROT_WRITE, 0x22, 0, 0) as _ }
+}
+
+fn mprotect(ptr: *mut u8, size: usize, protection: usize) {
+	unsafe {
+		syscall(SYS_MPROTECT as _, ptr as _, size, protection, 0, 0, 0);
+	}
 }
 
 fn munmap(ptr: *mut u8, size: usize) {
 	unsafe {
-		syscall(11, ptr as _, size, 0, 0, 0, 0);
+		syscall(SYS_MUNMAP as _, ptr as _, size, 0, 0, 0, 0);
 	}
 }
 
@@ -42,17 +59,34 @@ pub struct Executable {
 }
 
 impl Executable {
-	pub fn new(bytes: Vec<u8>, entry_point_offset: usize) -> Self {
-		let ptr = mmap(bytes.len());
+	/// Maps `program` into memory, with its code executable and its read-only
+	/// data on the pages right after it.
+	pub fn new(program: &x86_64::Program) -> Result<Self> {
+		let entry_point_offset = program.entry_point_offset.ok_or_else(|| {
+			Diagnostic::error("can't run a program without a `main` function to start at")
+		})?;
+
+		let rodata_offset = program.code.len().next_multiple_of(PAGE_SIZE);
+		let size = rodata_offset + program.rodata.len();
+
+		let code = program.relocate(rodata_offset);
+
+		let ptr = mmap(size);
 		unsafe {
-			std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+			std::ptr::copy_nonoverlapping(code.as_ptr(), ptr, code.len());
+
+			let rodata = ptr.add(rodata_offset);
+			std::ptr::copy_nonoverlapping(program.rodata.as_ptr(), rodata, program.rodata.len());
+
+			mprotect(ptr, rodata_offset, PROT_READ | PROT_EXEC);
+			mprotect(rodata, size - rodata_offset, PROT_READ);
 		}
 
-		Self {
+		Ok(Self {
 			ptr,
-			size: bytes.len(),
+			size,
 			entry_point_offset,
-		}
+		})
 	}
 
 	pub fn call(&self) -> usize {
@@ -63,3 +97,9 @@ impl Executable {
 		}
 	}
 }
+
+impl Drop for Executable {
+	fn drop(&mut self) {
+		munmap(self.ptr, self.size);
+	}
+}
@@ -0,0 +1,80 @@
+use crate::{
+	x86_64::{Memory, Register},
+	Diagnostic, Result,
+};
+
+/// The layout of a function's stack frame, addressed relative to `rbp` so
+/// that pushing onto the stack doesn't move anything:
+///
+/// ```text
+/// [rbp + 16 + 8 * n]  stack argument n
+/// [rbp + 8]           return address
+/// [rbp]               the caller's rbp
+/// [rbp - 8 - 8 * n]   slot n
+/// ```
+///
+/// `rsp` is 16-byte aligned before the `call` into the function, so after
+/// pushing `rbp` it's aligned again, and the size is rounded up to keep it
+/// that way for the calls the function makes.
+#[derive(Debug, Default)]
+pub struct Frame {
+	size: i32,
+}
+
+impl Frame {
+	pub fn new(function: &str, slot_count: usize) -> Result<Self> {
+		let size = (slot_count * 8).next_multiple_of(16);
+
+		let size = i32::try_from(size).map_err(|_| {
+			Diagnostic::error(format!(
+				"function `{function}` needs a stack frame of {size} bytes"
+			))
+			.with_note(format!(
+				"stack frames are addressed with 32-bit displacements, so they can be at most {} bytes",
+				i32::MAX as usize + 1 - 16
+			))
+		})?;
+
+		Ok(Self { size })
+	}
+
+	/// The number of bytes to reserve below `rbp`.
+	pub fn size(&self) -> i32 {
+		self.size
+	}
+
+	pub fn slot(&self, slot: usize) -> Memory {
+		Memory::new(Register::Rbp, -8 - slot as i32 * 8)
+	}
+
+	/// The `index`th argument passed on the stack, after the ones passed in
+	/// registers.
+	pub fn argument(&self, index: usize) -> Memory {
+		Memory::new(Register::Rbp, 16 + index as i32 * 8)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn keeps_the_stack_aligned() {
+		for (slot_count, size) in [(0, 0), (1, 16), (2, 16), (3, 32)] {
+			assert_eq!(Frame::new("f", slot_count).unwrap().size(), size);
+		}
+
+		let frame = Frame::new("f", 3).unwrap();
+		assert_eq!(frame.slot(0), Memory::new(Register::Rbp, -8));
+		assert_eq!(frame.slot(2), Memory::new(Register::Rbp, -24));
+		assert_eq!(frame.argument(1), Memory::new(Register::Rbp, 24));
+	}
+
+	#[test]
+	fn rejects_frames_too_large_to_address() {
+		let largest = (i32::MAX as usize + 1 - 16) / 8;
+
+		assert!(Frame::new("f", largest).is_ok());
+		assert!(Frame::new("f", largest + 1).is_err());
+	}
+}
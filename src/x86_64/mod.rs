@@ -1,5 +1,17 @@
+mod assembler;
 mod compiler;
+mod disassembler;
 mod executable;
+mod frame;
+mod register;
+mod register_allocator;
+mod syscall;
 
+pub use assembler::*;
 pub use compiler::*;
+pub use disassembler::*;
 pub use executable::*;
+pub use frame::*;
+pub use register::*;
+pub use register_allocator::*;
+pub use syscall::*;
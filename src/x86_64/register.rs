@@ -0,0 +1,42 @@
+/// A 64-bit general purpose register, in encoding order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Register {
+	Rax,
+	Rcx,
+	Rdx,
+	Rbx,
+	Rsp,
+	Rbp,
+	Rsi,
+	Rdi,
+	R8,
+	R9,
+	R10,
+	R11,
+	R12,
+	R13,
+	R14,
+	R15,
+}
+
+impl Register {
+	/// The number that encodes the register, split between ModRM or SIB and
+	/// a REX bit for `r8` and up.
+	pub fn index(self) -> u8 {
+		self as u8
+	}
+
+	/// Whether the System V ABI makes functions preserve the register.
+	pub fn is_callee_saved(self) -> bool {
+		matches!(
+			self,
+			Register::Rbx
+				| Register::Rsp
+				| Register::Rbp
+				| Register::R12
+				| Register::R13
+				| Register::R14
+				| Register::R15
+		)
+	}
+}
@@ -0,0 +1,245 @@
+use crate::{llir, x86_64::Register};
+
+/// Registers handed out to `llir` registers, caller saved ones first so that
+/// callee saved ones, which cost a save and restore, are only used when
+/// needed. `rax`, `rcx` and `rdx` are scratch registers for the compiler, and
+/// the other argument registers are left alone so calls can be set up
+/// without shuffling.
+const ALLOCATABLE: [Register; 7] = [
+	Register::R10,
+	Register::R11,
+	Register::Rbx,
+	Register::R12,
+	Register::R13,
+	Register::R14,
+	Register::R15,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Location {
+	Register(Register),
+	/// Index of an 8 byte slot in the stack frame.
+	Stack(usize),
+}
+
+/// Where every `llir::Register` of a function lives.
+#[derive(Debug, Default)]
+pub struct Allocation {
+	locations: Vec<Option<Location>>,
+	/// Callee saved registers the function uses, with the slots they're saved
+	/// in until it returns.
+	pub saved: Vec<(Register, usize)>,
+	pub slot_count: usize,
+}
+
+impl Allocation {
+	/// Gives every register its own stack slot, which is slow but makes the
+	/// generated code easy to follow.
+	pub fn spill_all(function: &llir::Function) -> Self {
+		Self {
+			locations: (0..function.register_count)
+				.map(|x| Some(Location::Stack(x)))
+				.collect(),
+			saved: Vec::new(),
+			slot_count: function.register_count,
+		}
+	}
+
+	/// Assigns registers with linear scan, spilling whichever interval ends
+	/// last when they run out. Intervals that live across a call only get
+	/// callee saved registers.
+	pub fn linear_scan(function: &llir::Function) -> Self {
+		let mut ret = Self {
+			locations: vec![None; function.register_count],
+			saved: Vec::new(),
+			slot_count: 0,
+		};
+
+		let calls = function
+			.body
+			.iter()
+			.enumerate()
+			.filter(|(_, x)| x.is_call())
+			.map(|(index, _)| index)
+			.collect::<Vec<_>>();
+
+		let mut active = Vec::<(llir::Interval, Register)>::new();
+
+		for interval in llir::live_intervals(function) {
+			active.retain(|(x, _)| x.end >= interval.start);
+
+			let crosses_call = calls.iter().any(|x| interval.spans(*x));
+			let usable = |x: Register| !crosses_call || x.is_callee_saved();
+
+			let free = ALLOCATABLE
+				.into_iter()
+				.find(|x| usable(*x) && active.iter().all(|(_, y)| x != y));
+
+			if let Some(register) = free {
+				ret.assign(interval.register, Location::Register(register));
+				active.push((interval, register));
+
+				continue;
+			}
+
+			let victim = active
+				.iter()
+				.enumerate()
+				.filter(|(_, (_, x))| usable(*x))
+				.max_by_key(|(_, (x, _))| x.end)
+				.map(|(index, _)| index);
+
+			match victim {
+				Some(index) if active[index].0.end > interval.end => {
+					let (spilled, register) = active.remove(index);
+
+					let slot = ret.slot();
+					ret.assign(spilled.register, Location::Stack(slot));
+					ret.assign(interval.register, Location::Register(register));
+
+					active.push((interval, register));
+				}
+				_ => {
+					let slot = ret.slot();
+					ret.assign(interval.register, Location::Stack(slot));
+				}
+			}
+		}
+
+		let mut used = ret
+			.locations
+			.iter()
+			.filter_map(|x| match x {
+				Some(Location::Register(x)) if x.is_callee_saved() => Some(*x),
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+
+		used.sort_by_key(|x| x.index());
+		used.dedup();
+
+		for register in used {
+			let slot = ret.slot();
+			ret.saved.push((register, slot));
+		}
+
+		ret
+	}
+
+	pub fn location(&self, register: llir::Register) -> Location {
+		self.locations[register.0].expect("register is never used")
+	}
+
+	fn assign(&mut self, register: llir::Register, location: Location) {
+		self.locations[register.0] = Some(location);
+	}
+
+	fn slot(&mut self) -> usize {
+		self.slot_count += 1;
+		self.slot_count - 1
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::x86_64::{Compiler, Executable};
+	use llir::Node;
+
+	fn function(register_count: usize, body: Vec<Node>) -> llir::Function {
+		let mut ret = llir::Function::new("main".into(), true, Vec::new());
+		ret.register_count = register_count;
+		ret.body = body;
+		ret
+	}
+
+	#[test]
+	fn only_callee_saved_registers_survive_calls() {
+		let function = function(
+			4,
+			vec![
+				Node::MoveImmI64 {
+					dst: llir::Register(0),
+					imm: 1,
+				},
+				Node::MoveImmI64 {
+					dst: llir::Register(1),
+					imm: 2,
+				},
+				Node::PrintI64 {
+					dst: llir::Register(2),
+					value: llir::Register(1),
+				},
+				Node::Add {
+					dst: llir::Register(3),
+					lhs: llir::Register(0),
+					rhs: llir::Register(2),
+				},
+				Node::Return {
+					value: Some(llir::Register(3)),
+				},
+			],
+		);
+
+		let allocation = Allocation::linear_scan(&function);
+
+		// `r0` is needed after the call, `r1` only by it
+		assert_eq!(
+			allocation.location(llir::Register(0)),
+			Location::Register(Register::Rbx)
+		);
+		assert_eq!(
+			allocation.location(llir::Register(1)),
+			Location::Register(Register::R10)
+		);
+		assert_eq!(allocation.saved, [(Register::Rbx, 0)]);
+	}
+
+	/// Adds up ten powers of two that are all live at once, so some of them
+	/// end up on the stack.
+	fn sum_of_ten() -> llir::Function {
+		let mut body = (0..10)
+			.map(|x| Node::MoveImmI64 {
+				dst: llir::Register(x),
+				imm: 1 << x,
+			})
+			.collect::<Vec<_>>();
+
+		let mut sum = llir::Register(0);
+
+		for x in 1..10 {
+			let dst = llir::Register(9 + x);
+
+			body.push(Node::Add {
+				dst,
+				lhs: sum,
+				rhs: llir::Register(x),
+			});
+
+			sum = dst;
+		}
+
+		body.push(Node::Return { value: Some(sum) });
+		function(19, body)
+	}
+
+	#[test]
+	fn spills_under_register_pressure() {
+		let allocation = Allocation::linear_scan(&sum_of_ten());
+
+		let spilled = (0..10)
+			.filter(|x| matches!(allocation.location(llir::Register(*x)), Location::Stack(_)))
+			.count();
+
+		assert!(spilled >= 10 - ALLOCATABLE.len(), "{spilled}");
+
+		for spill_all in [false, true] {
+			let program = Compiler::new()
+				.with_spill_all(spill_all)
+				.compile(vec![sum_of_ten()])
+				.unwrap();
+
+			assert_eq!(Executable::new(&program).unwrap().call(), 1023);
+		}
+	}
+}
@@ -0,0 +1,8 @@
+//! Linux x86_64 syscall numbers, shared by everything that issues syscalls
+//! either at compile time or from generated code.
+
+pub const SYS_WRITE: u32 = 1;
+pub const SYS_MMAP: u32 = 9;
+pub const SYS_MPROTECT: u32 = 10;
+pub const SYS_MUNMAP: u32 = 11;
+pub const SYS_EXIT: u32 = 60;
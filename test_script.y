@@ -1,10 +1,11 @@
-fn add(a: i32) {
+/// Returns `a` plus 500.
+fn add(a: i64) -> i64 {
 	return a + 500;
 }
 
-fn main() {
+fn main() -> i64 {
 	let asdf = 1 + 2 * 3 + 4;
-	let zxcv = 5 * 1;
+	let zxcv = 5 * 1; // never zero
 
 	if zxcv == 0 {
 		return add(400);